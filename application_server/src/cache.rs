@@ -0,0 +1,126 @@
+// src/cache.rs
+use crate::models::MenuResponse;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// In-memory cache of the menu list, enabled by `MENU_CACHE_TTL`
+/// Menu handlers that change menus must call `invalidate` so readers never see stale data.
+/// Readers note the `generation` before loading the list, so a list read before an
+/// invalidation is never stored after it.
+#[derive(Debug, Default)]
+pub struct MenuCache {
+    ttl: Option<Duration>,
+    generation: AtomicU64,
+    entry: RwLock<Option<(Instant, Vec<MenuResponse>)>>,
+}
+
+impl MenuCache {
+    /// Create a cache keeping the menu list for `ttl`, or a disabled cache when `ttl` is None
+    pub fn new(ttl: Option<Duration>) -> MenuCache {
+        MenuCache {
+            ttl,
+            generation: AtomicU64::new(0),
+            entry: RwLock::new(None),
+        }
+    }
+
+    /// Get the cached menu list if caching is enabled and the entry hasn't expired
+    pub fn get(&self) -> Option<Vec<MenuResponse>> {
+        let ttl = self.ttl?;
+        let entry = self.entry.read().unwrap_or_else(|err| err.into_inner());
+        match entry.as_ref() {
+            Some((cached_at, menus)) if cached_at.elapsed() < ttl => Some(menus.clone()),
+            _ => None,
+        }
+    }
+
+    /// Current generation, to be read before loading the menu list from the database
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Store a menu list loaded at `generation`, unless the cache was invalidated since
+    pub fn set(&self, generation: u64, menus: &[MenuResponse]) {
+        if self.ttl.is_some() {
+            let mut entry = self.entry.write().unwrap_or_else(|err| err.into_inner());
+            // Invalidation bumps the generation under the same lock, so this check can't race it
+            if self.generation() == generation {
+                *entry = Some((Instant::now(), menus.to_vec()));
+            }
+        }
+    }
+
+    /// Drop the cached menu list so the next read goes to the database
+    pub fn invalidate(&self) {
+        let mut entry = self.entry.write().unwrap_or_else(|err| err.into_inner());
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *entry = None;
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Build a menu list with a single entry
+    fn menus(name: &str) -> Vec<MenuResponse> {
+        vec![MenuResponse {
+            id: 1,
+            name: name.to_string(),
+            price: 0,
+            category: None,
+        }]
+    }
+
+    // Test Case: A disabled cache never returns anything
+    #[test]
+    fn test_disabled_cache() {
+        let cache = MenuCache::new(None);
+        cache.set(cache.generation(), &menus("M-01"));
+        assert!(cache.get().is_none());
+    }
+
+    // Test Case: A cached list is returned until it is invalidated
+    #[test]
+    fn test_cache_invalidation() {
+        let cache = MenuCache::new(Some(Duration::from_secs(60)));
+        assert!(cache.get().is_none());
+        cache.set(cache.generation(), &menus("M-01"));
+        assert_eq!(
+            cache.get().map(|menus| menus[0].name.clone()),
+            Some("M-01".to_string())
+        );
+        cache.invalidate();
+        assert!(cache.get().is_none());
+    }
+
+    // Test Case: A cached list expires after the TTL
+    #[test]
+    fn test_cache_expiry() {
+        let cache = MenuCache::new(Some(Duration::from_millis(10)));
+        cache.set(cache.generation(), &menus("M-01"));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(cache.get().is_none());
+    }
+
+    // Test Case: A list read before an invalidation isn't stored after it
+    #[test]
+    fn test_cache_set_after_invalidation() {
+        let cache = MenuCache::new(Some(Duration::from_secs(60)));
+        // A reader misses the cache and loads the list, then a write invalidates the cache
+        let generation = cache.generation();
+        let stale = menus("M-01");
+        cache.invalidate();
+        cache.set(generation, &stale);
+        assert!(cache.get().is_none());
+
+        // A list loaded after the invalidation is cached as usual
+        cache.set(cache.generation(), &menus("M-02"));
+        assert_eq!(
+            cache.get().map(|menus| menus[0].name.clone()),
+            Some("M-02".to_string())
+        );
+    }
+}
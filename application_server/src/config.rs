@@ -0,0 +1,425 @@
+// src/config.rs
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Longest restaurant name accepted from `RESTAURANT_NAME`
+const MAX_RESTAURANT_NAME_LENGTH: usize = 64;
+
+/// Represents the application configuration, loaded once at startup from the environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Address the server binds to (`HOST`)
+    pub host: IpAddr,
+    /// Port the server listens on (`PORT`)
+    pub port: u16,
+    /// Path of the SQLite database file (`DATABASE_PATH`)
+    pub db_path: String,
+    /// Milliseconds a connection waits for another one's lock before failing, 0 to fail at once (`DB_BUSY_TIMEOUT_MS`)
+    pub db_busy_timeout_ms: u64,
+    /// Restaurant name shown by the info endpoint (`RESTAURANT_NAME`)
+    pub restaurant_name: String,
+    /// Seconds the menu list is cached for, 0 to disable caching (`MENU_CACHE_TTL`)
+    pub menu_cache_ttl_secs: u64,
+    /// Punctuation allowed in menu names besides letters, digits and spaces;
+    /// None accepts any characters (`MENU_NAME_CHARS`)
+    pub menu_name_chars: Option<String>,
+    /// Shortest cooking time a menu can be calibrated to, in minutes (`MIN_COOKING_TIME`)
+    pub min_cooking_time: u64,
+    /// Longest cooking time a menu can be calibrated to, in minutes (`MAX_COOKING_TIME`)
+    pub max_cooking_time: u64,
+    /// Most units of one item a single order request can add (`MAX_QUANTITY_PER_REQUEST`)
+    pub max_quantity_per_request: u64,
+    /// Most tables that can be created, 0 for unlimited (`MAX_TABLES`)
+    pub max_tables: u64,
+    /// Most menus that can be created, 0 for unlimited (`MAX_MENUS`)
+    pub max_menus: u64,
+    /// Seconds an order creation `Idempotency-Key` is remembered for (`IDEMPOTENCY_KEY_TTL`)
+    pub idempotency_key_ttl_secs: u64,
+    /// Sales tax added to receipts, as a fraction of the subtotal (`TAX_RATE`)
+    pub tax_rate: f64,
+    /// Indent JSON error bodies for readability; success bodies stay compact (`PRETTY_ERRORS`)
+    pub pretty_errors: bool,
+    /// Requests handled at once before new ones get a 503, 0 for unlimited (`MAX_CONCURRENT_REQUESTS`)
+    pub max_concurrent_requests: usize,
+    /// Processing timeout for requests without an `X-Request-Timeout-Ms` header, 0 for none (`REQUEST_TIMEOUT_MS`)
+    pub request_timeout_ms: u64,
+    /// Longest timeout a client can ask for with `X-Request-Timeout-Ms` (`MAX_REQUEST_TIMEOUT_MS`)
+    pub max_request_timeout_ms: u64,
+    /// Reject requests whose `Accept` header excludes JSON with a 406 (`ENFORCE_ACCEPT_JSON`)
+    pub enforce_accept_json: bool,
+    /// Serve the /admin endpoints, which expose the whole database (`ADMIN_ENDPOINTS`)
+    pub admin_endpoints: bool,
+    /// Paths answered with 404 as if their routes didn't exist, including the paths below them (`DISABLED_ROUTES`)
+    pub disabled_routes: Vec<String>,
+    /// Browser origins allowed to call the API, or `*` for any (`CORS_ALLOWED_ORIGINS`)
+    pub cors_allowed_origins: Vec<String>,
+    /// Log request and response bodies, for troubleshooting only (`LOG_BODIES`)
+    pub log_bodies: bool,
+    /// Artificial latency added to every request, for testing only (`CHAOS_DELAY_MS`)
+    pub chaos_delay_ms: u64,
+    /// Fraction of requests failed with a 500, for testing only (`CHAOS_ERROR_RATE`)
+    pub chaos_error_rate: f64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 3030,
+            db_path: "restaurant.db".to_string(),
+            db_busy_timeout_ms: 5_000,
+            restaurant_name: "Restaurant".to_string(),
+            menu_cache_ttl_secs: 0,
+            menu_name_chars: None,
+            min_cooking_time: 1,
+            max_cooking_time: 120,
+            max_quantity_per_request: 1000,
+            max_tables: 0,
+            max_menus: 0,
+            idempotency_key_ttl_secs: 86_400,
+            tax_rate: 0.0,
+            // On by default while developing, off in release builds
+            pretty_errors: cfg!(debug_assertions),
+            max_concurrent_requests: 0,
+            request_timeout_ms: 30_000,
+            max_request_timeout_ms: 60_000,
+            enforce_accept_json: false,
+            admin_endpoints: false,
+            disabled_routes: vec![],
+            cors_allowed_origins: vec!["http://localhost:3000".to_string()],
+            log_bodies: false,
+            chaos_delay_ms: 0,
+            chaos_error_rate: 0.0,
+        }
+    }
+}
+
+impl Config {
+    /// Load the configuration from the process environment
+    pub fn from_env() -> Result<Config, String> {
+        Config::from_vars(&std::env::vars().collect())
+    }
+
+    /// Build the configuration from a map of environment values, falling back to defaults
+    /// for anything unset and validating everything that is set
+    pub fn from_vars(vars: &HashMap<String, String>) -> Result<Config, String> {
+        let defaults = Config::default();
+        let config = Config {
+            host: parse_var(vars, "HOST", defaults.host)?,
+            port: parse_var(vars, "PORT", defaults.port)?,
+            db_path: vars
+                .get("DATABASE_PATH")
+                .map(|value| value.trim().to_string())
+                .unwrap_or(defaults.db_path),
+            db_busy_timeout_ms: parse_var(vars, "DB_BUSY_TIMEOUT_MS", defaults.db_busy_timeout_ms)?,
+            restaurant_name: vars
+                .get("RESTAURANT_NAME")
+                .map(|value| value.trim().to_string())
+                .unwrap_or(defaults.restaurant_name),
+            menu_cache_ttl_secs: parse_var(vars, "MENU_CACHE_TTL", defaults.menu_cache_ttl_secs)?,
+            menu_name_chars: vars
+                .get("MENU_NAME_CHARS")
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .or(defaults.menu_name_chars),
+            min_cooking_time: parse_var(vars, "MIN_COOKING_TIME", defaults.min_cooking_time)?,
+            max_cooking_time: parse_var(vars, "MAX_COOKING_TIME", defaults.max_cooking_time)?,
+            max_quantity_per_request: parse_var(
+                vars,
+                "MAX_QUANTITY_PER_REQUEST",
+                defaults.max_quantity_per_request,
+            )?,
+            max_tables: parse_var(vars, "MAX_TABLES", defaults.max_tables)?,
+            max_menus: parse_var(vars, "MAX_MENUS", defaults.max_menus)?,
+            idempotency_key_ttl_secs: parse_var(
+                vars,
+                "IDEMPOTENCY_KEY_TTL",
+                defaults.idempotency_key_ttl_secs,
+            )?,
+            tax_rate: parse_var(vars, "TAX_RATE", defaults.tax_rate)?,
+            pretty_errors: parse_flag(vars, "PRETTY_ERRORS", defaults.pretty_errors)?,
+            max_concurrent_requests: parse_var(
+                vars,
+                "MAX_CONCURRENT_REQUESTS",
+                defaults.max_concurrent_requests,
+            )?,
+            request_timeout_ms: parse_var(vars, "REQUEST_TIMEOUT_MS", defaults.request_timeout_ms)?,
+            max_request_timeout_ms: parse_var(
+                vars,
+                "MAX_REQUEST_TIMEOUT_MS",
+                defaults.max_request_timeout_ms,
+            )?,
+            enforce_accept_json: parse_flag(
+                vars,
+                "ENFORCE_ACCEPT_JSON",
+                defaults.enforce_accept_json,
+            )?,
+            admin_endpoints: parse_flag(vars, "ADMIN_ENDPOINTS", defaults.admin_endpoints)?,
+            disabled_routes: vars
+                .get("DISABLED_ROUTES")
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|path| path.trim().trim_end_matches('/').to_string())
+                        .filter(|path| !path.is_empty())
+                        .collect()
+                })
+                .unwrap_or(defaults.disabled_routes),
+            cors_allowed_origins: vars
+                .get("CORS_ALLOWED_ORIGINS")
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|origin| origin.trim().trim_end_matches('/').to_string())
+                        .filter(|origin| !origin.is_empty())
+                        .collect()
+                })
+                .unwrap_or(defaults.cors_allowed_origins),
+            log_bodies: parse_flag(vars, "LOG_BODIES", defaults.log_bodies)?,
+            chaos_delay_ms: parse_var(vars, "CHAOS_DELAY_MS", defaults.chaos_delay_ms)?,
+            chaos_error_rate: parse_var(vars, "CHAOS_ERROR_RATE", defaults.chaos_error_rate)?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check the values that parsed correctly but still make no sense together
+    fn validate(&self) -> Result<(), String> {
+        if self.port == 0 {
+            return Err("PORT must be greater than 0".to_string());
+        }
+        if self.db_path.is_empty() {
+            return Err("DATABASE_PATH must not be empty".to_string());
+        }
+        if self.restaurant_name.is_empty() {
+            return Err("RESTAURANT_NAME must not be empty".to_string());
+        }
+        if self.restaurant_name.chars().count() > MAX_RESTAURANT_NAME_LENGTH {
+            return Err(format!(
+                "RESTAURANT_NAME must be at most {} characters",
+                MAX_RESTAURANT_NAME_LENGTH
+            ));
+        }
+        if self.min_cooking_time == 0 {
+            return Err("MIN_COOKING_TIME must be greater than 0".to_string());
+        }
+        if self.min_cooking_time > self.max_cooking_time {
+            return Err("MIN_COOKING_TIME must not exceed MAX_COOKING_TIME".to_string());
+        }
+        if self.max_quantity_per_request == 0 {
+            return Err("MAX_QUANTITY_PER_REQUEST must be greater than 0".to_string());
+        }
+        if self.idempotency_key_ttl_secs == 0 {
+            return Err("IDEMPOTENCY_KEY_TTL must be greater than 0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.tax_rate) {
+            return Err("TAX_RATE must be between 0 and 1".to_string());
+        }
+        if self.max_request_timeout_ms == 0 {
+            return Err("MAX_REQUEST_TIMEOUT_MS must be greater than 0".to_string());
+        }
+        if self.request_timeout_ms > self.max_request_timeout_ms {
+            return Err("REQUEST_TIMEOUT_MS must not exceed MAX_REQUEST_TIMEOUT_MS".to_string());
+        }
+        if let Some(path) = self
+            .disabled_routes
+            .iter()
+            .find(|path| !path.starts_with('/'))
+        {
+            return Err(format!(
+                "DISABLED_ROUTES paths must start with '/': {:?}",
+                path
+            ));
+        }
+        if let Some(origin) = self.cors_allowed_origins.iter().find(|origin| {
+            let host = origin
+                .strip_prefix("http://")
+                .or_else(|| origin.strip_prefix("https://"));
+            *origin != "*" && !host.is_some_and(|host| !host.is_empty() && !host.contains('/'))
+        }) {
+            return Err(format!(
+                "CORS_ALLOWED_ORIGINS must be http(s) origins or '*': {:?}",
+                origin
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.chaos_error_rate) {
+            return Err("CHAOS_ERROR_RATE must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// How long a database connection waits for a lock before failing with `SQLITE_BUSY`
+    pub fn db_busy_timeout(&self) -> Duration {
+        Duration::from_millis(self.db_busy_timeout_ms)
+    }
+
+    /// How long the menu list may be cached for, None when caching is disabled
+    pub fn menu_cache_ttl(&self) -> Option<Duration> {
+        (self.menu_cache_ttl_secs > 0).then(|| Duration::from_secs(self.menu_cache_ttl_secs))
+    }
+
+    /// Whether `path`, without its API version prefix, is or is below a disabled route
+    pub fn route_disabled(&self, path: &str) -> bool {
+        self.disabled_routes.iter().any(|disabled| {
+            path.strip_prefix(disabled.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    /// Socket address the server should listen on
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(self.host, self.port)
+    }
+}
+
+/// Parse an optional environment value, returning the default when it is unset
+fn parse_var<T: FromStr>(
+    vars: &HashMap<String, String>,
+    key: &str,
+    default: T,
+) -> Result<T, String> {
+    match vars.get(key) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid value for {}: {:?}", key, value)),
+        None => Ok(default),
+    }
+}
+
+/// Parse an optional on/off environment value, returning the default when it is unset
+fn parse_flag(vars: &HashMap<String, String>, key: &str, default: bool) -> Result<bool, String> {
+    match vars.get(key).map(|value| value.trim().to_ascii_lowercase()) {
+        Some(value) => match value.as_str() {
+            "1" | "true" | "yes" | "on" => Ok(true),
+            "0" | "false" | "no" | "off" | "" => Ok(false),
+            _ => Err(format!("Invalid value for {}: {:?}", key, value)),
+        },
+        None => Ok(default),
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Build an environment map from key/value pairs
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    // Test Case: An empty environment yields the defaults
+    #[test]
+    fn test_config_defaults() {
+        let config = Config::from_vars(&vars(&[])).expect("Defaults should be valid");
+        assert_eq!(config, Config::default());
+        assert_eq!(config.menu_cache_ttl(), None);
+        assert_eq!(config.addr().to_string(), "127.0.0.1:3030");
+    }
+
+    // Test Case: Set values are parsed and override the defaults
+    #[test]
+    fn test_config_from_vars() {
+        let config = Config::from_vars(&vars(&[
+            ("HOST", "0.0.0.0"),
+            ("PORT", " 8080 "),
+            ("DATABASE_PATH", "/tmp/test.db"),
+            ("DB_BUSY_TIMEOUT_MS", "1500"),
+            ("RESTAURANT_NAME", " Chez Rust "),
+            ("MENU_CACHE_TTL", "30"),
+            ("MENU_NAME_CHARS", " -'& "),
+            ("MIN_COOKING_TIME", "2"),
+            ("MAX_COOKING_TIME", "90"),
+            ("MAX_QUANTITY_PER_REQUEST", "20"),
+            ("MAX_TABLES", "10"),
+            ("MAX_MENUS", "25"),
+            ("IDEMPOTENCY_KEY_TTL", "600"),
+            ("TAX_RATE", "0.0825"),
+            ("PRETTY_ERRORS", "off"),
+            ("MAX_CONCURRENT_REQUESTS", "64"),
+            ("REQUEST_TIMEOUT_MS", "0"),
+            ("MAX_REQUEST_TIMEOUT_MS", "10000"),
+            ("ENFORCE_ACCEPT_JSON", "yes"),
+            ("ADMIN_ENDPOINTS", "true"),
+            ("DISABLED_ROUTES", " /orders/validate, /admin/ ,"),
+            (
+                "CORS_ALLOWED_ORIGINS",
+                "https://dashboard.example.com/, http://localhost:8080",
+            ),
+            ("LOG_BODIES", "1"),
+            ("CHAOS_DELAY_MS", "250"),
+            ("CHAOS_ERROR_RATE", "0.1"),
+        ]))
+        .expect("Config should be valid");
+        assert_eq!(config.addr().to_string(), "0.0.0.0:8080");
+        assert_eq!(config.db_path, "/tmp/test.db");
+        assert_eq!(config.db_busy_timeout(), Duration::from_millis(1500));
+        assert_eq!(config.restaurant_name, "Chez Rust");
+        assert_eq!(config.menu_cache_ttl(), Some(Duration::from_secs(30)));
+        assert_eq!(config.menu_name_chars.as_deref(), Some("-'&"));
+        assert_eq!((config.min_cooking_time, config.max_cooking_time), (2, 90));
+        assert_eq!(config.max_quantity_per_request, 20);
+        assert_eq!((config.max_tables, config.max_menus), (10, 25));
+        assert_eq!(config.idempotency_key_ttl_secs, 600);
+        assert_eq!(config.tax_rate, 0.0825);
+        assert!(!config.pretty_errors);
+        assert_eq!(config.max_concurrent_requests, 64);
+        assert_eq!(
+            (config.request_timeout_ms, config.max_request_timeout_ms),
+            (0, 10000)
+        );
+        assert!(config.enforce_accept_json);
+        assert!(config.admin_endpoints);
+        assert_eq!(config.disabled_routes, vec!["/orders/validate", "/admin"]);
+        assert!(config.route_disabled("/admin/dump.sql"));
+        assert!(config.route_disabled("/orders/validate"));
+        assert!(!config.route_disabled("/orders/validated"));
+        assert!(!config.route_disabled("/orders"));
+        assert_eq!(
+            config.cors_allowed_origins,
+            vec!["https://dashboard.example.com", "http://localhost:8080"]
+        );
+        assert!(config.log_bodies);
+        assert_eq!(config.chaos_delay_ms, 250);
+        assert_eq!(config.chaos_error_rate, 0.1);
+    }
+
+    // Test Case: Malformed or invalid values are rejected
+    #[test]
+    fn test_config_validation() {
+        assert!(Config::from_vars(&vars(&[("PORT", "not-a-port")])).is_err());
+        assert!(Config::from_vars(&vars(&[("PORT", "0")])).is_err());
+        assert!(Config::from_vars(&vars(&[("HOST", "localhost:80")])).is_err());
+        assert!(Config::from_vars(&vars(&[("DATABASE_PATH", "  ")])).is_err());
+        assert!(Config::from_vars(&vars(&[("DB_BUSY_TIMEOUT_MS", "-5")])).is_err());
+        assert!(Config::from_vars(&vars(&[("RESTAURANT_NAME", "")])).is_err());
+        assert!(Config::from_vars(&vars(&[("RESTAURANT_NAME", &"x".repeat(65))])).is_err());
+        assert!(Config::from_vars(&vars(&[("LOG_BODIES", "maybe")])).is_err());
+        assert!(Config::from_vars(&vars(&[("MAX_TABLES", "-1")])).is_err());
+        assert!(Config::from_vars(&vars(&[("MIN_COOKING_TIME", "0")])).is_err());
+        assert!(Config::from_vars(&vars(&[
+            ("MIN_COOKING_TIME", "30"),
+            ("MAX_COOKING_TIME", "20")
+        ]))
+        .is_err());
+        assert!(Config::from_vars(&vars(&[("MAX_QUANTITY_PER_REQUEST", "0")])).is_err());
+        assert!(Config::from_vars(&vars(&[("IDEMPOTENCY_KEY_TTL", "0")])).is_err());
+        assert!(Config::from_vars(&vars(&[("TAX_RATE", "-0.1")])).is_err());
+        assert!(Config::from_vars(&vars(&[("TAX_RATE", "8")])).is_err());
+        assert!(Config::from_vars(&vars(&[("MAX_REQUEST_TIMEOUT_MS", "0")])).is_err());
+        assert!(Config::from_vars(&vars(&[("REQUEST_TIMEOUT_MS", "90000")])).is_err());
+        assert!(Config::from_vars(&vars(&[("DISABLED_ROUTES", "/tables,orders")])).is_err());
+        assert!(Config::from_vars(&vars(&[("CORS_ALLOWED_ORIGINS", "localhost:3000")])).is_err());
+        assert!(
+            Config::from_vars(&vars(&[("CORS_ALLOWED_ORIGINS", "http://localhost/app")])).is_err()
+        );
+        assert!(Config::from_vars(&vars(&[("CHAOS_ERROR_RATE", "1.5")])).is_err());
+        assert!(Config::from_vars(&vars(&[("CHAOS_ERROR_RATE", "NaN")])).is_err());
+    }
+}
@@ -1,31 +1,119 @@
-use rusqlite::Connection;
+use r2d2::{Pool, PooledConnection};
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use std::time::Duration;
 
-/// Establish a connection to the SQLite database
-pub fn get_db_conn() -> Connection {
-    let conn = Connection::open("restaurant.db").expect("Failed to open SQLite connection");
-    conn
+/// Pool of open connections to the SQLite database, shared by every route
+pub type DbPool = Pool<SqliteConnectionManager>;
+
+/// Connection checked out of the pool and returned to it when dropped.
+/// It dereferences to a `rusqlite::Connection`, so handlers use it like one.
+pub type DbConn = PooledConnection<SqliteConnectionManager>;
+
+/// Name of the rule keeping order items' cooking time and quantity from going negative.
+/// Writes breaking it fail with an error message naming it.
+pub const ORDER_ITEMS_NON_NEGATIVE: &str = "order_items_non_negative";
+
+/// Establish a connection to the SQLite database, failing if the file can't be opened
+pub fn get_db_conn(db_path: &str, busy_timeout: Duration) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(db_path)?;
+    configure_connection(&conn, busy_timeout)?;
+    Ok(conn)
+}
+
+/// Set up a newly opened connection: it waits up to `busy_timeout` for another connection's
+/// lock instead of failing with `SQLITE_BUSY`, and the database uses write-ahead logging so
+/// readers and the writer don't block each other.
+pub fn configure_connection(conn: &Connection, busy_timeout: Duration) -> rusqlite::Result<()> {
+    conn.busy_timeout(busy_timeout)?;
+    // Setting the journal mode answers with the resulting mode, which is ignored
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |_row| Ok(()))
+}
+
+/// Create the connection pool for the SQLite database. Its idle connections are opened in the
+/// background, so a database that can't be opened fails the checkouts instead of the server.
+pub fn init_pool(db_path: &str, busy_timeout: Duration) -> DbPool {
+    let manager = SqliteConnectionManager::file(db_path)
+        .with_init(move |conn| configure_connection(conn, busy_timeout));
+    Pool::builder().build_unchecked(manager)
+}
+
+/// Run `work` inside a single transaction, committing when it returns `Ok` and rolling back
+/// when it returns `Err`, so multi-step writes never leave partial state behind.
+/// The write lock is taken up front so concurrent writers wait on the busy timeout
+/// instead of failing when a read lock can't be upgraded.
+pub fn run_in_transaction<T, E, F>(conn: &mut Connection, work: F) -> Result<T, E>
+where
+    F: FnOnce(&Transaction) -> Result<T, E>,
+    E: From<rusqlite::Error>,
+{
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    // Dropping the transaction without committing rolls it back
+    let value = work(&tx)?;
+    tx.commit()?;
+    Ok(value)
 }
 
 /// Initialize the database and create necessary tables
-pub fn initialize_db() {
+pub fn initialize_db(db_path: &str, busy_timeout: Duration) {
     println!("Initializing the database...");
-    let conn = Connection::open("restaurant.db").expect("Failed to open SQLite connection");
+    let conn = get_db_conn(db_path, busy_timeout).expect("Failed to open SQLite connection");
+    initialize_schema(&conn);
+}
 
+/// Create the necessary tables on an open connection
+pub fn initialize_schema(conn: &Connection) {
     // Enable foreign key support
     conn.execute("PRAGMA foreign_keys = ON;", [])
         .expect("Failed to enable foreign key support");
 
     println!("Creating 'tables' table");
-    create_table_table_if_not_exists(&conn).expect("Failed to create 'tables' table");
+    create_table_table_if_not_exists(conn).expect("Failed to create 'tables' table");
 
     println!("Creating 'menus' table");
-    create_menu_table_if_not_exists(&conn).expect("Failed to create 'menus' table");
+    create_menu_table_if_not_exists(conn).expect("Failed to create 'menus' table");
 
     println!("Creating 'orders' table");
-    create_order_table_if_not_exists(&conn).expect("Failed to create 'orders' table");
+    create_order_table_if_not_exists(conn).expect("Failed to create 'orders' table");
 
     println!("Creating 'order_items' table");
-    create_order_item_table_if_not_exists(&conn).expect("Failed to create 'order_items' table");
+    create_order_item_table_if_not_exists(conn).expect("Failed to create 'order_items' table");
+
+    println!("Creating 'order_summary' table");
+    create_order_summary_table_if_not_exists(conn).expect("Failed to create 'order_summary' table");
+
+    println!("Creating 'idempotency_keys' table");
+    create_idempotency_key_table_if_not_exists(conn)
+        .expect("Failed to create 'idempotency_keys' table");
+
+    println!("Creating 'healthcheck' table");
+    create_healthcheck_table_if_not_exists(conn).expect("Failed to create 'healthcheck' table");
+}
+
+/// Confirm the database is writable by inserting a sentinel row and reading it back.
+/// The transaction is always rolled back, so the check leaves nothing behind.
+/// Returns the reason on failure, e.g. a read-only file or a full disk.
+pub fn check_writable(conn: &mut Connection) -> Result<(), String> {
+    let sentinel = format!("{:016x}", rand::random::<u64>());
+    let tx = conn.transaction().map_err(|err| err.to_string())?;
+    tx.execute(
+        "INSERT INTO healthcheck (sentinel) VALUES (?1)",
+        [&sentinel],
+    )
+    .map_err(|err| err.to_string())?;
+    let read_back: String = tx
+        .query_row(
+            "SELECT sentinel FROM healthcheck WHERE id = ?1",
+            [tx.last_insert_rowid()],
+            |row| row.get(0),
+        )
+        .map_err(|err| err.to_string())?;
+    tx.rollback().map_err(|err| err.to_string())?;
+    if read_back != sentinel {
+        return Err("Sentinel row read back with a different value".to_string());
+    }
+    Ok(())
 }
 
 /// Create the 'tables' table if it doesn't exist
@@ -40,20 +128,410 @@ fn create_table_table_if_not_exists(conn: &Connection) -> rusqlite::Result<()> {
 /// Create the 'menus' table if it doesn't exist
 fn create_menu_table_if_not_exists(conn: &Connection) -> rusqlite::Result<()> {
     conn.execute(
-        "CREATE TABLE IF NOT EXISTS menus (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+        "CREATE TABLE IF NOT EXISTS menus (id INTEGER PRIMARY KEY, name TEXT NOT NULL, position INTEGER, cooking_time INTEGER, price INTEGER NOT NULL DEFAULT 0, category TEXT)",
         [],
     )?;
+    // Display position, added after the table was first released
+    add_column_if_missing(conn, "menus", "position", "INTEGER")?;
+    // Calibrated cooking time for new order items, added after the table was first released
+    add_column_if_missing(conn, "menus", "cooking_time", "INTEGER")?;
+    // Price in cents, added after the table was first released
+    add_column_if_missing(conn, "menus", "price", "INTEGER NOT NULL DEFAULT 0")?;
+    // Optional category such as "Drinks", added after the table was first released
+    add_column_if_missing(conn, "menus", "category", "TEXT")?;
     Ok(())
 }
 
 /// Create the 'orders' table if it doesn't exist
 fn create_order_table_if_not_exists(conn: &Connection) -> rusqlite::Result<()> {
-    conn.execute("CREATE TABLE IF NOT EXISTS orders (id INTEGER PRIMARY KEY, table_id INTEGER NOT NULL, FOREIGN KEY (table_id) REFERENCES tables(id), UNIQUE (table_id))",[])?;
+    conn.execute("CREATE TABLE IF NOT EXISTS orders (id INTEGER PRIMARY KEY, table_id INTEGER NOT NULL, status TEXT NOT NULL DEFAULT 'pending', created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (table_id) REFERENCES tables(id), UNIQUE (table_id))",[])?;
+    // Lifecycle status, added after the table was first released
+    add_column_if_missing(conn, "orders", "status", "TEXT NOT NULL DEFAULT 'pending'")?;
+    // Placement time, added after the table was first released. SQLite can't add a column
+    // defaulting to CURRENT_TIMESTAMP, so upgraded databases stamp new orders with a trigger
+    // and orders placed before the upgrade get the time of the upgrade.
+    if add_column_if_missing(conn, "orders", "created_at", "TEXT")? {
+        conn.execute("UPDATE orders SET created_at = CURRENT_TIMESTAMP", [])?;
+    }
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS orders_created_at_insert AFTER INSERT ON orders
+        WHEN NEW.created_at IS NULL BEGIN
+            UPDATE orders SET created_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+        END",
+        [],
+    )?;
     Ok(())
 }
 
 /// Create the 'order_items' table if it doesn't exist
 fn create_order_item_table_if_not_exists(conn: &Connection) -> rusqlite::Result<()> {
-    conn.execute("CREATE TABLE IF NOT EXISTS order_items (id INTEGER PRIMARY KEY, order_id INTEGER NOT NULL, menu_id INTEGER NOT NULL, cooking_time INTEGER NOT NULL, quantity INTEGER NOT NULL default 1, FOREIGN KEY (order_id) REFERENCES orders(id), FOREIGN KEY (menu_id) REFERENCES menus(id))",[])?;
+    conn.execute("CREATE TABLE IF NOT EXISTS order_items (id INTEGER PRIMARY KEY, order_id INTEGER NOT NULL, menu_id INTEGER NOT NULL, cooking_time INTEGER NOT NULL, quantity INTEGER NOT NULL default 1, unit_cooking_time INTEGER, served BOOLEAN NOT NULL DEFAULT 0, FOREIGN KEY (order_id) REFERENCES orders(id), FOREIGN KEY (menu_id) REFERENCES menus(id), CONSTRAINT order_items_non_negative CHECK (cooking_time >= 0 AND quantity >= 0))",[])?;
+    // SQLite can't add a CHECK constraint to an existing table, so tables created before it
+    // enforce the same rule with triggers
+    let has_check: bool = conn.query_row(
+        "SELECT sql LIKE '%CONSTRAINT ' || ?1 || ' CHECK%' FROM sqlite_master
+        WHERE type = 'table' AND name = 'order_items'",
+        [ORDER_ITEMS_NON_NEGATIVE],
+        |row| row.get(0),
+    )?;
+    if !has_check {
+        for event in ["INSERT", "UPDATE"] {
+            conn.execute(
+                &format!(
+                    "CREATE TRIGGER IF NOT EXISTS {rule}_{suffix} BEFORE {event} ON order_items
+                    WHEN NEW.cooking_time < 0 OR NEW.quantity < 0 BEGIN
+                        SELECT RAISE(ABORT, 'CHECK constraint failed: {rule}');
+                    END",
+                    rule = ORDER_ITEMS_NON_NEGATIVE,
+                    suffix = event.to_lowercase(),
+                ),
+                [],
+            )?;
+        }
+    }
+    // Cooking time of a single unit, added after the table was first released. `cooking_time`
+    // is kept equal to it times `quantity`, so quantity changes never round the per-unit time.
+    if add_column_if_missing(conn, "order_items", "unit_cooking_time", "INTEGER")? {
+        conn.execute(
+            "UPDATE order_items SET unit_cooking_time = cooking_time / quantity,
+            cooking_time = (cooking_time / quantity) * quantity",
+            [],
+        )?;
+    }
+    // Whether the item has been delivered to the table, added after the table was first released
+    add_column_if_missing(conn, "order_items", "served", "BOOLEAN NOT NULL DEFAULT 0")?;
+    // Items inserted with only a total cooking time derive their unit time from it
+    conn.execute(
+        "CREATE TRIGGER IF NOT EXISTS order_items_unit_cooking_time_insert AFTER INSERT ON order_items
+        WHEN NEW.unit_cooking_time IS NULL BEGIN
+            UPDATE order_items SET unit_cooking_time = NEW.cooking_time / NEW.quantity WHERE id = NEW.id;
+        END",
+        [],
+    )?;
     Ok(())
 }
+
+/// Create the 'order_summary' table if it doesn't exist, along with the triggers maintaining it.
+/// It holds each order's totals so the order list doesn't recompute them on every read.
+fn create_order_summary_table_if_not_exists(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS order_summary (order_id INTEGER PRIMARY KEY, table_code TEXT, total_cooking_time INTEGER NOT NULL DEFAULT 0, item_count INTEGER NOT NULL DEFAULT 0, total_quantity INTEGER NOT NULL DEFAULT 0);
+
+        -- Every order gets a summary row when it's created and loses it when it's deleted
+        CREATE TRIGGER IF NOT EXISTS order_summary_order_insert AFTER INSERT ON orders BEGIN
+            INSERT OR REPLACE INTO order_summary (order_id, table_code)
+            VALUES (NEW.id, (SELECT code FROM tables WHERE id = NEW.table_id));
+        END;
+        CREATE TRIGGER IF NOT EXISTS order_summary_order_delete AFTER DELETE ON orders BEGIN
+            DELETE FROM order_summary WHERE order_id = OLD.id;
+        END;
+
+        -- Totals are recomputed for the affected order, which only touches that order's items
+        CREATE TRIGGER IF NOT EXISTS order_summary_item_insert AFTER INSERT ON order_items BEGIN
+            UPDATE order_summary SET (total_cooking_time, item_count, total_quantity) =
+                (SELECT COALESCE(SUM(cooking_time), 0), COUNT(*), COALESCE(SUM(quantity), 0) FROM order_items WHERE order_id = NEW.order_id)
+            WHERE order_id = NEW.order_id;
+        END;
+        CREATE TRIGGER IF NOT EXISTS order_summary_item_update AFTER UPDATE ON order_items BEGIN
+            UPDATE order_summary SET (total_cooking_time, item_count, total_quantity) =
+                (SELECT COALESCE(SUM(cooking_time), 0), COUNT(*), COALESCE(SUM(quantity), 0) FROM order_items WHERE order_id = order_summary.order_id)
+            WHERE order_id IN (OLD.order_id, NEW.order_id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS order_summary_item_delete AFTER DELETE ON order_items BEGIN
+            UPDATE order_summary SET (total_cooking_time, item_count, total_quantity) =
+                (SELECT COALESCE(SUM(cooking_time), 0), COUNT(*), COALESCE(SUM(quantity), 0) FROM order_items WHERE order_id = OLD.order_id)
+            WHERE order_id = OLD.order_id;
+        END;
+
+        -- Table codes follow the tables they were copied from
+        CREATE TRIGGER IF NOT EXISTS order_summary_table_update AFTER UPDATE OF code ON tables BEGIN
+            UPDATE order_summary SET table_code = NEW.code
+            WHERE order_id IN (SELECT id FROM orders WHERE table_id = NEW.id);
+        END;
+        CREATE TRIGGER IF NOT EXISTS order_summary_table_delete AFTER DELETE ON tables BEGIN
+            UPDATE order_summary SET table_code = NULL
+            WHERE order_id IN (SELECT id FROM orders WHERE table_id = OLD.id);
+        END;
+
+        -- Summarize orders placed before the table existed
+        INSERT INTO order_summary (order_id, table_code, total_cooking_time, item_count, total_quantity)
+        SELECT orders.id, t.code, COALESCE(SUM(oi.cooking_time), 0), COUNT(oi.id), COALESCE(SUM(oi.quantity), 0)
+        FROM orders
+        LEFT JOIN tables AS t ON t.id = orders.table_id
+        LEFT JOIN order_items AS oi ON oi.order_id = orders.id
+        WHERE orders.id NOT IN (SELECT order_id FROM order_summary)
+        GROUP BY orders.id;",
+    )
+}
+
+/// Create the 'idempotency_keys' table if it doesn't exist.
+/// It holds the request and response of each keyed order creation, stamped in Unix seconds.
+fn create_idempotency_key_table_if_not_exists(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS idempotency_keys (key TEXT PRIMARY KEY, status INTEGER NOT NULL, location TEXT, body TEXT NOT NULL, created_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER)), request TEXT)",
+        [],
+    )?;
+    // The request the key was used with, added after the table was first released
+    add_column_if_missing(conn, "idempotency_keys", "request", "TEXT")?;
+    Ok(())
+}
+
+/// Create the 'healthcheck' table if it doesn't exist.
+/// The deep health check writes to it inside a transaction it rolls back, so it stays empty.
+fn create_healthcheck_table_if_not_exists(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS healthcheck (id INTEGER PRIMARY KEY, sentinel TEXT NOT NULL)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Add a column to an existing table unless it is already there,
+/// so databases created by earlier versions pick up new columns on startup.
+/// Returns whether the column was added.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<bool> {
+    let exists = conn
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1",
+            table
+        ))?
+        .exists([column])?;
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(!exists)
+}
+
+/// Write the schema and every row of the database as SQL statements that rebuild it in a fresh
+/// SQLite database. Statements are handed to `write` one at a time; returning false stops the dump.
+pub fn dump_sql(conn: &Connection, mut write: impl FnMut(String) -> bool) -> rusqlite::Result<()> {
+    // Read everything in one transaction, so writes committed during a slow download can't
+    // leave the dump mixing rows from before and after them. Dropping it ends the read.
+    let tx = conn.unchecked_transaction()?;
+    // Tables come first so their rows are in place before indexes and triggers are created
+    let mut stmt = tx.prepare(
+        "SELECT type, name, sql FROM sqlite_master
+        WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+        ORDER BY type = 'table' DESC, rowid",
+    )?;
+    let entries = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    // Rows are inserted in table order, so foreign keys are only checked once everything is loaded
+    if !write("PRAGMA foreign_keys=OFF;\nBEGIN TRANSACTION;\n".to_string()) {
+        return Ok(());
+    }
+    for (kind, name, sql) in entries {
+        if !write(format!("{};\n", sql)) {
+            return Ok(());
+        }
+        if kind != "table" {
+            continue;
+        }
+        let table = quote_identifier(&name);
+        let mut rows_stmt = tx.prepare(&format!("SELECT * FROM {}", table))?;
+        let column_count = rows_stmt.column_count();
+        let mut rows = rows_stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let values = (0..column_count)
+                .map(|index| row.get_ref(index).map(sql_literal))
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            if !write(format!(
+                "INSERT INTO {} VALUES({});\n",
+                table,
+                values.join(",")
+            )) {
+                return Ok(());
+            }
+        }
+    }
+    write("COMMIT;\n".to_string());
+    Ok(())
+}
+
+/// Quote a table name for use in generated SQL
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Render a stored value as an SQL literal that reads back as the same value and type
+fn sql_literal(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(value) => value.to_string(),
+        // Debug formatting keeps a decimal point so whole numbers stay REAL
+        ValueRef::Real(value) if value.is_finite() => format!("{:?}", value),
+        ValueRef::Real(value) if value > 0.0 => "1e999".to_string(),
+        ValueRef::Real(_) => "-1e999".to_string(),
+        ValueRef::Text(text) => format!("'{}'", String::from_utf8_lossy(text).replace('\'', "''")),
+        ValueRef::Blob(blob) => {
+            let hex: String = blob.iter().map(|byte| format!("{:02X}", byte)).collect();
+            format!("X'{}'", hex)
+        }
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::handlers::tests::setup_static_data;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    // Remove a test database file along with the WAL files SQLite keeps next to it
+    pub(crate) fn remove_db_files(path: &Path) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.as_os_str().to_owned();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
+    }
+
+    // Test Case: Writes committed while a dump is streaming don't show up in it
+    #[test]
+    fn test_dump_sql_is_a_snapshot() {
+        let path = std::env::temp_dir().join(format!(
+            "restaurant-test-{}-dump-snapshot.db",
+            std::process::id()
+        ));
+        remove_db_files(&path);
+        let db_path = path.to_string_lossy().to_string();
+        initialize_db(&db_path, Duration::from_secs(5));
+        let conn = get_db_conn(&db_path, Duration::from_secs(5))
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        conn.execute_batch(
+            "INSERT INTO orders (table_id) VALUES (1);
+            INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (1, 1, 6);",
+        )
+        .expect("Failed to insert order data");
+
+        let rows = |conn: &Connection| -> Vec<String> {
+            let mut stmt = conn
+                .prepare(
+                    "SELECT 'order ' || id || ' table ' || table_id FROM orders
+                    UNION ALL SELECT 'item ' || order_id || '/' || menu_id || ' x' || quantity FROM order_items
+                    UNION ALL SELECT 'summary ' || order_id || ' x' || total_quantity FROM order_summary
+                    UNION ALL SELECT type || ' ' || name FROM sqlite_master WHERE name NOT LIKE 'sqlite_%'
+                    ORDER BY 1",
+                )
+                .expect("Failed to prepare query");
+            stmt.query_map([], |row| row.get(0))
+                .expect("Failed to run query")
+                .collect::<Result<_, _>>()
+                .expect("Failed to read rows")
+        };
+        let snapshot = rows(&conn);
+
+        // Another connection writes as soon as the dump has started
+        let writer = get_db_conn(&db_path, Duration::from_secs(5))
+            .expect("Failed to open SQLite connection");
+        let mut written = false;
+        let mut dump = String::new();
+        dump_sql(&conn, |statement| {
+            if !written {
+                writer
+                    .execute_batch(
+                        "INSERT INTO orders (table_id) VALUES (2);
+                        INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (2, 3, 4);
+                        UPDATE order_items SET quantity = 5 WHERE order_id = 1;
+                        CREATE TABLE late_table (id INTEGER PRIMARY KEY);",
+                    )
+                    .expect("Failed to write during the dump");
+                written = true;
+            }
+            dump.push_str(&statement);
+            true
+        })
+        .expect("Failed to dump the database");
+        assert_ne!(rows(&conn), snapshot);
+
+        // The dump restores the database as it was when the dump started
+        let restored = Connection::open_in_memory().expect("Failed to create test database");
+        restored.execute_batch(&dump).expect("Failed to load dump");
+        assert_eq!(rows(&restored), snapshot);
+
+        drop((conn, writer));
+        remove_db_files(&path);
+    }
+
+    // Test Case: A writer waits for another connection's write lock instead of failing at once
+    #[test]
+    fn test_busy_timeout_waits_for_lock() {
+        let path = std::env::temp_dir().join(format!(
+            "restaurant-test-{}-busy-timeout.db",
+            std::process::id()
+        ));
+        remove_db_files(&path);
+        let db_path = path.to_string_lossy().to_string();
+        initialize_db(&db_path, Duration::from_secs(5));
+        let mut waiting = get_db_conn(&db_path, Duration::from_secs(5))
+            .expect("Failed to open SQLite connection");
+        let impatient =
+            get_db_conn(&db_path, Duration::ZERO).expect("Failed to open SQLite connection");
+        let journal_mode: String = waiting
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .expect("Failed to read journal mode");
+        assert_eq!(journal_mode, "wal");
+
+        // Another connection holds the write lock for a moment
+        let (locked, is_locked) = mpsc::channel();
+        let holder = {
+            let db_path = db_path.clone();
+            std::thread::spawn(move || {
+                let mut conn = get_db_conn(&db_path, Duration::from_secs(5))
+                    .expect("Failed to open SQLite connection");
+                run_in_transaction(&mut conn, |tx| -> rusqlite::Result<()> {
+                    tx.execute("INSERT INTO tables (code) VALUES ('T-01')", [])?;
+                    locked.send(()).expect("Failed to signal the lock");
+                    std::thread::sleep(Duration::from_millis(300));
+                    Ok(())
+                })
+                .expect("Failed to hold the write lock");
+            })
+        };
+        is_locked.recv().expect("Failed to wait for the lock");
+
+        // Without a timeout the write fails immediately with SQLITE_BUSY
+        let err = impatient
+            .execute("INSERT INTO tables (code) VALUES ('T-02')", [])
+            .expect_err("Expected the database to be busy");
+        assert_eq!(
+            err.sqlite_error_code(),
+            Some(rusqlite::ErrorCode::DatabaseBusy)
+        );
+
+        // With one, the write waits for the lock to be released and then succeeds
+        let started = Instant::now();
+        run_in_transaction(&mut waiting, |tx| {
+            tx.execute("INSERT INTO tables (code) VALUES ('T-02')", [])
+        })
+        .expect("Expected the write to wait for the lock");
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        holder.join().expect("Lock holder panicked");
+
+        let count: i64 = waiting
+            .query_row("SELECT COUNT(*) FROM tables", [], |row| row.get(0))
+            .expect("Failed to count tables");
+        assert_eq!(count, 2);
+        drop((waiting, impatient));
+        remove_db_files(&path);
+    }
+}
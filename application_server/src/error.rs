@@ -0,0 +1,78 @@
+// src/error.rs
+use crate::db::ORDER_ITEMS_NON_NEGATIVE;
+use warp::http::StatusCode;
+
+/// Errors a handler can reject a request with.
+/// `handle_rejection` answers them with a `{"error": ..., "code": ...}` body and the matching status.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiError {
+    /// The requested resource doesn't exist
+    NotFound(String),
+    /// The request conflicts with the current state of a resource
+    Conflict(String),
+    /// The request itself is invalid
+    BadRequest(String),
+    /// A write broke one of the database's integrity rules, which valid requests never do
+    Integrity(String),
+    /// Something failed on the server; details are logged, never sent to the client
+    Internal,
+}
+
+impl warp::reject::Reject for ApiError {}
+
+impl ApiError {
+    /// HTTP status the error is answered with
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Integrity(_) | ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable machine-readable code for the `code` field
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Integrity(_) => "integrity_violation",
+            ApiError::Internal => "internal",
+        }
+    }
+
+    /// Human-readable message for the `error` field
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::NotFound(message)
+            | ApiError::Conflict(message)
+            | ApiError::BadRequest(message)
+            | ApiError::Integrity(message) => message,
+            ApiError::Internal => "Internal server error",
+        }
+    }
+}
+
+/// Database failures are logged and reported as internal errors, naming the broken rule if any
+impl From<rusqlite::Error> for ApiError {
+    fn from(err: rusqlite::Error) -> Self {
+        tracing::error!(error = %err, "database error");
+        match integrity_violation(&err) {
+            Some(message) => ApiError::Integrity(message.to_string()),
+            None => ApiError::Internal,
+        }
+    }
+}
+
+/// Client-facing description of the integrity rule a failed write broke, if it broke one
+pub fn integrity_violation(err: &rusqlite::Error) -> Option<&'static str> {
+    match err {
+        rusqlite::Error::SqliteFailure(_, Some(message))
+            if message.contains(ORDER_ITEMS_NON_NEGATIVE) =>
+        {
+            Some("Order item cooking time and quantity must not be negative")
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,119 @@
+// src/events.rs
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use warp::ws::{Message, WebSocket};
+
+/// Events buffered for each websocket before a slow client starts missing them
+const ORDER_EVENT_CAPACITY: usize = 256;
+
+/// Represents the kinds of change made to an order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// Represents a change to an order, pushed to the kitchen over `/ws/orders`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub event: OrderEventKind,
+    pub order_id: i64,
+}
+
+impl OrderEvent {
+    /// An event of the given kind for an order
+    pub fn new(event: OrderEventKind, order_id: i64) -> OrderEvent {
+        OrderEvent { event, order_id }
+    }
+}
+
+/// Broadcast channel carrying order changes from the order handlers to every connected websocket
+/// Handlers publish once their transaction has committed, so clients never see rolled back changes.
+#[derive(Debug)]
+pub struct OrderEvents {
+    sender: broadcast::Sender<OrderEvent>,
+}
+
+impl Default for OrderEvents {
+    fn default() -> Self {
+        OrderEvents {
+            sender: broadcast::channel(ORDER_EVENT_CAPACITY).0,
+        }
+    }
+}
+
+impl OrderEvents {
+    /// Send an event to every subscriber; without subscribers it is dropped
+    pub fn publish(&self, event: OrderEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<OrderEvent> {
+        self.sender.subscribe()
+    }
+}
+
+/// Send `snapshot` over the socket, then each event from `events` as JSON until the client leaves
+pub async fn push_order_events(
+    socket: WebSocket,
+    snapshot: String,
+    mut events: broadcast::Receiver<OrderEvent>,
+) {
+    let (mut sender, mut receiver) = socket.split();
+    if sender.send(Message::text(snapshot)).await.is_err() {
+        return;
+    }
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else {
+                        continue;
+                    };
+                    if sender.send(Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                // A client too slow to keep up misses the oldest events and carries on
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "websocket client missed order events");
+                }
+                Err(RecvError::Closed) => break,
+            },
+            // Messages from the client are ignored until it closes the socket
+            message = receiver.next() => match message {
+                Some(Ok(message)) if !message.is_close() => {}
+                _ => break,
+            },
+        }
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test Case: Events reach subscribers that joined before they were published, as snake_case JSON
+    #[tokio::test]
+    async fn test_order_events() {
+        let events = OrderEvents::default();
+        // Publishing without subscribers is not an error
+        events.publish(OrderEvent::new(OrderEventKind::Created, 1));
+
+        let mut receiver = events.subscribe();
+        events.publish(OrderEvent::new(OrderEventKind::Deleted, 2));
+        let event = receiver.recv().await.expect("Expected an event");
+        assert_eq!(event, OrderEvent::new(OrderEventKind::Deleted, 2));
+        assert_eq!(
+            serde_json::to_value(&event).expect("Failed to serialize event"),
+            serde_json::json!({"event": "deleted", "order_id": 2})
+        );
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -1,579 +1,3867 @@
+use crate::cache::MenuCache;
+use crate::config::Config;
+use crate::db::{check_writable, dump_sql, get_db_conn, run_in_transaction, DbConn as Connection};
+use crate::error::{integrity_violation, ApiError};
+use crate::events::{push_order_events, OrderEvent, OrderEventKind, OrderEvents};
+use crate::locks::TableLocks;
 use crate::models::{
-    Menu, MenuResponse, OrderItem, OrderItemResponse, OrderRequestBody, OrderResponse, Table,
-    TableResponse,
+    get_current_state, CopyOrderRequestBody, EnsureMenusRequestBody, IdempotentResponse,
+    ItemQuantityRequestBody, KitchenLoad, LargestOrdersQuery, Menu, MenuCookingTimeRequestBody,
+    MenuDeletion, MenuListQuery, MenuResponse, MenuSearchQuery, OrderItem, OrderItemResponse,
+    OrderListQuery, OrderRequestBody, OrderResponse, OrderSizeMeasure, OrderStatusRequestBody,
+    OrderSummary, Receipt, ReorderMenusRequestBody, SortDirection, Table, TableCreation,
+    TableDeletion, TableIdsQuery, TableLoadQuery, TableLoadResponse, TableResponse,
+    ACTIVE_ORDER_FILTER, ORDER_STATUSES,
 };
 use rand::Rng;
 use rusqlite::params;
-use rusqlite::Connection;
+use serde::Serialize;
 use serde_json::json;
-use warp;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Instant;
+use warp::Reply;
 
-// Handlers for Table operations
+/// Bytes of SQL collected before a chunk of the database dump is sent
+const DUMP_CHUNK_BYTES: usize = 8 * 1024;
 
-/// List all tables
-pub async fn list_table_handler(conn: Connection) -> Result<impl warp::Reply, warp::Rejection> {
-    match Table::list(&conn) {
-        Ok(tables) => Ok(warp::reply::with_status(
-            warp::reply::json(&tables),
+/// Orders listed per page when the request doesn't set `limit`
+const DEFAULT_ORDER_PAGE_SIZE: u32 = 50;
+
+/// Most orders listed per page; larger `limit` values are clamped to it
+const MAX_ORDER_PAGE_SIZE: u32 = 200;
+
+/// Most tables whose items can be fetched in one call to the items-by-tables listing
+const MAX_TABLE_IDS: usize = 50;
+
+/// Longest `Idempotency-Key` accepted on order creation
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Error response from handler work that runs inside a database transaction
+struct HandlerError {
+    status: warp::http::StatusCode,
+    message: String,
+}
+
+impl HandlerError {
+    /// An error with the given status and message
+    fn new(status: warp::http::StatusCode, message: impl Into<String>) -> HandlerError {
+        HandlerError {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// An internal server error with the given message
+    fn internal(message: impl Into<String>) -> HandlerError {
+        HandlerError::new(warp::http::StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+
+    /// Turn the error into a JSON error response
+    fn into_response(self) -> warp::reply::Response {
+        warp::reply::with_status(
+            warp::reply::json(&json!({ "error": self.message })),
+            self.status,
+        )
+        .into_response()
+    }
+}
+
+/// Failures to begin or commit the transaction itself
+impl From<rusqlite::Error> for HandlerError {
+    fn from(err: rusqlite::Error) -> Self {
+        tracing::error!(error = %err, "database transaction failed");
+        HandlerError::internal(integrity_violation(&err).unwrap_or("Database transaction failed"))
+    }
+}
+
+/// JSON response with the given status, or a sanitized 500 when the value can't be serialized.
+/// Use it for computed responses such as reports, where `warp::reply::json` would log the
+/// failure and answer with an empty 500 body. Non-finite floats serialize as `null`.
+fn json_reply<T: Serialize>(value: &T, status: warp::http::StatusCode) -> warp::reply::Response {
+    match serde_json::to_vec(value) {
+        Ok(body) => json_body_reply(body, status),
+        Err(_err) => {
+            // If the value can't be serialized, log why and return an internal server error status with a generic message
+            tracing::error!(error = %_err, "failed to serialize response");
+            HandlerError::internal("Failed to serialize response").into_response()
+        }
+    }
+}
+
+/// Response with the given status carrying an already serialized JSON body
+fn json_body_reply(
+    body: impl Into<warp::hyper::Body>,
+    status: warp::http::StatusCode,
+) -> warp::reply::Response {
+    let mut resp = warp::reply::Response::new(body.into());
+    *resp.status_mut() = status;
+    resp.headers_mut().insert(
+        warp::http::header::CONTENT_TYPE,
+        warp::http::HeaderValue::from_static("application/json"),
+    );
+    resp
+}
+
+/// Fail with a conflict when a table would hold `total` rows, more than `limit` (0 is unlimited)
+fn check_limit(total: i64, limit: u64) -> Result<(), HandlerError> {
+    if limit > 0 && total as u64 > limit {
+        return Err(HandlerError::new(
+            warp::http::StatusCode::CONFLICT,
+            "Resource limit reached",
+        ));
+    }
+    Ok(())
+}
+
+// Handlers for general operations
+
+/// Describe the running server: restaurant name, version and uptime
+pub async fn info_handler(
+    config: Arc<Config>,
+    started_at: Instant,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    Ok(warp::reply::with_status(
+        warp::reply::json(&json!({
+            "name": config.restaurant_name,
+            "version": env!("CARGO_PKG_VERSION"),
+            "uptime_seconds": started_at.elapsed().as_secs(),
+        })),
+        warp::http::StatusCode::OK,
+    ))
+}
+
+/// Check that the database answers a trivial query
+pub async fn health_handler(conn: Connection) -> Result<impl warp::Reply, warp::Rejection> {
+    match conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0)) {
+        Ok(_) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"status": "ok"})),
             warp::http::StatusCode::OK,
         )),
         Err(_err) => {
-            // If an error occurs while fetching the tables, return an empty array with an internal server error status
+            // If the query fails, report the service as degraded
+            tracing::warn!(error = %_err, "health check failed");
             Ok(warp::reply::with_status(
-                warp::reply::json::<Vec<TableResponse>>(&vec![]),
-                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                warp::reply::json(&json!({"status": "degraded"})),
+                warp::http::StatusCode::SERVICE_UNAVAILABLE,
             ))
         }
     }
 }
 
-/// Create a new table
-pub async fn create_table_handler(
-    conn: Connection,
-    data: Table,
+/// Check that the database is writable with a full write and read-back cycle
+pub async fn deep_health_handler(
+    mut conn: Connection,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    match Table::get_existing_table_id(&conn, &data) {
-        Ok(Some(table_id)) => {
-            // If the table already exists, return the existing table ID with a created status
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({ "id": table_id })),
-                warp::http::StatusCode::CREATED,
-            ))
-        }
-        Ok(None) => {
-            // If the table does not exist, create a new one
-            match Table::create(&conn, &data) {
-                Ok(table_id) => Ok(warp::reply::with_status(
-                    warp::reply::json(&json!({ "id": table_id })),
-                    warp::http::StatusCode::CREATED,
-                )),
-                Err(_err) => {
-                    // If table creation fails, return an internal server error status with an error message
-                    Ok(warp::reply::with_status(
-                        warp::reply::json(&json!({"error":"Error creating table"})),
-                        warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    ))
-                }
-            }
-        }
-        Err(_err) => {
-            // If there is an error checking for the existing table, return an internal server error status with an error message
+    match check_writable(&mut conn) {
+        Ok(()) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"status": "ok"})),
+            warp::http::StatusCode::OK,
+        )),
+        Err(reason) => {
+            // If the write or read-back fails, report the database as unavailable with the reason
+            tracing::warn!(reason = reason.as_str(), "deep health check failed");
             Ok(warp::reply::with_status(
-                warp::reply::json(&json!({"error":"Error creating table"})),
-                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                warp::reply::json(&json!({"status": "unavailable", "error": reason})),
+                warp::http::StatusCode::SERVICE_UNAVAILABLE,
             ))
         }
     }
 }
 
-// Handlers for Menu operations
+// Handlers for Table operations
 
-/// List all menus
-pub async fn list_menu_handler(conn: Connection) -> Result<impl warp::Reply, warp::Rejection> {
-    match Menu::list(&conn) {
-        Ok(menus) => Ok(warp::reply::with_status(
-            warp::reply::json(&menus),
+/// List all tables
+pub async fn list_table_handler(conn: Connection) -> Result<impl warp::Reply, warp::Rejection> {
+    match Table::list(&conn) {
+        Ok(tables) => Ok(warp::reply::with_status(
+            warp::reply::json(&tables),
             warp::http::StatusCode::OK,
         )),
         Err(_err) => {
-            // If an error occurs while fetching the menus, return an empty array with an internal server error status
+            // If an error occurs while fetching the tables, return an empty array with an internal server error status
             Ok(warp::reply::with_status(
-                warp::reply::json::<Vec<MenuResponse>>(&vec![]),
+                warp::reply::json::<Vec<TableResponse>>(&vec![]),
                 warp::http::StatusCode::INTERNAL_SERVER_ERROR,
             ))
         }
     }
 }
 
-/// Create a new menu
-pub async fn create_menu_handler(
+/// List all tables by the load of their open order, least loaded first unless `direction=desc`
+pub async fn list_tables_by_load_handler(
     conn: Connection,
-    data: Menu,
+    query: TableLoadQuery,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    match Menu::get_existing_menu_id(&conn, &data) {
-        Ok(Some(menu_id)) => {
-            // If the menu already exists, return the existing menu ID with a created status
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({ "id": menu_id })),
-                warp::http::StatusCode::CREATED,
-            ))
-        }
-        Ok(None) => {
-            // If the menu does not exist, create a new one
-            match Menu::create(&conn, &data) {
-                Ok(menu_id) => Ok(warp::reply::with_status(
-                    warp::reply::json(&json!({ "id": menu_id })),
-                    warp::http::StatusCode::CREATED,
-                )),
-                Err(_err) => {
-                    // If menu creation fails, return an internal server error status with an error message
-                    Ok(warp::reply::with_status(
-                        warp::reply::json(&json!({ "error": "Error creating Menu" })),
-                        warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    ))
-                }
-            }
-        }
+    let direction = query.direction.unwrap_or(SortDirection::Asc);
+    match Table::list_by_load(&conn, direction) {
+        Ok(tables) => Ok(json_reply(&tables, warp::http::StatusCode::OK)),
         Err(_err) => {
-            // If there is an error checking for the existing menu, return an internal server error status with an error message
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({ "error": "Error creating Menu" })),
+            // If an error occurs while fetching the tables, return an empty array with an internal server error status
+            tracing::error!(error = %_err, "failed to list tables by load");
+            Ok(json_reply(
+                &Vec::<TableLoadResponse>::new(),
                 warp::http::StatusCode::INTERNAL_SERVER_ERROR,
             ))
         }
     }
 }
 
-// Handlers for Order operations
-
-/// Create a new order
-pub async fn create_order_handler(
+/// List the tables that currently have an order, with its total cooking time
+pub async fn occupied_tables_handler(
     conn: Connection,
-    req_body: OrderRequestBody,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let table_id = req_body.table_id;
-    let menu_ids = req_body.menu_ids;
-    if menu_ids.is_empty() {
-        // Return BAD REQUEST if no menu items are provided
-        return Ok(warp::reply::with_status(
-            warp::reply::json(&json!({"error":"Please Add Items"})),
-            warp::http::StatusCode::BAD_REQUEST,
-        ));
+    match Table::list_occupied(&conn) {
+        Ok(tables) => Ok(json_reply(&tables, warp::http::StatusCode::OK)),
+        // If an error occurs while fetching the tables, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
     }
+}
 
-    match OrderResponse::get_existing_order_id(&conn, table_id) {
-        Ok(Some(order_id)) => {
-            // If an active order exists, update the order items
-            for menu_id in menu_ids {
-                // Generate a random cooking time for the order item
-                let cooking_time = rand::thread_rng().gen_range(5..=15);
-                match OrderItem::get_existing_order_item_id(&conn, order_id, menu_id) {
-                    Ok(Some(order_item_id)) => {
-                        // If order item exists, update its quantity
-                        match OrderItem::add_quantity_of_existing_order_item(&conn, order_item_id) {
-                            Ok(_) => continue,
-                            Err(_) => {
-                                // Respond with an error if updating the order item fails
-                                return Ok(warp::reply::with_status(
-                                    warp::reply::json(
-                                        &json!({"error":"Error updating order Item"}),
-                                    ),
-                                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                ));
-                            }
-                        }
-                    }
-                    Ok(None) => {
-                        // If order item does not exist, create a new one
-                        match OrderItem::create(&conn, order_id, menu_id, cooking_time) {
-                            Ok(_) => continue,
-                            Err(_err) => {
-                                // Respond with an error if creating the order item fails
-                                eprintln!("{}", _err);
-                                return Ok(warp::reply::with_status(
-                                    warp::reply::json(
-                                        &json!({"error":"Error creating order Item"}),
-                                    ),
-                                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                ));
-                            }
-                        }
-                    }
-                    Err(_err) => {
-                        // Respond with an error if there is an issue checking for the existing order item
-                        return Ok(warp::reply::with_status(
-                            warp::reply::json(
-                                &json!({"error":"Error checking for existing order Item"}),
-                            ),
-                            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                        ));
-                    }
-                }
-            }
+/// List the cooking time each table's open order still needs, most loaded first
+pub async fn kitchen_load_handler(conn: Connection) -> Result<impl warp::Reply, warp::Rejection> {
+    match KitchenLoad::list(&conn) {
+        Ok(loads) => Ok(json_reply(&loads, warp::http::StatusCode::OK)),
+        // If an error occurs while aggregating the loads, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
 
-            // If all order items were successfully handled, return a success message
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({"success":"All order items updated successfully"})),
-                warp::http::StatusCode::OK,
-            ))
-        }
-        Ok(None) => {
-            // If no active order exists, create a new order and order items
-            match OrderResponse::create(&conn, table_id) {
-                Ok(last_inserted_id) => {
-                    for menu_id in menu_ids {
-                        // Generate a random cooking time for each order item
-                        let cooking_time = rand::thread_rng().gen_range(5..=15);
-                        match OrderItem::create(&conn, last_inserted_id, menu_id, cooking_time) {
-                            Ok(_) => continue,
-                            Err(_err) => {
-                                // Respond with an error if creating an order item fails
-                                eprintln!("{}", _err);
-                                return Ok(warp::reply::with_status(
-                                    warp::reply::json(
-                                        &json!({"error":"Error creating order Item"}),
-                                    ),
-                                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                ));
-                            }
-                        }
-                    }
+/// Get a single table by ID
+pub async fn get_table_handler(
+    conn: Connection,
+    table_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Table::get_by_id(&conn, table_id) {
+        Ok(Some(table)) => Ok(json_reply(&table, warp::http::StatusCode::OK)),
+        // If the table does not exist, reject the request as not found
+        Ok(None) => Err(warp::reject::custom(ApiError::NotFound(
+            "Table not found".to_string(),
+        ))),
+        // If an error occurs while fetching the table, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
 
-                    // If the order and all order items were successfully created, return a success message with the new order ID
-                    Ok(warp::reply::with_status(
-                        warp::reply::json(
-                            &json!({"id":last_inserted_id, "success":"Order and all order items created successfully"}),
-                        ),
-                        warp::http::StatusCode::CREATED,
-                    ))
-                }
-                Err(_err) => {
-                    // Respond with an error if creating the order fails
-                    Ok(warp::reply::with_status(
-                        warp::reply::json(
-                            &json!({"error":format!("Error creating order {}", _err)}),
-                        ),
-                        warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                    ))
-                }
+/// Create a new table
+pub async fn create_table_handler(
+    mut conn: Connection,
+    config: Arc<Config>,
+    data: Table,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Count and insert in one transaction so concurrent requests can't exceed MAX_TABLES
+    let result = run_in_transaction(&mut conn, |tx| {
+        match Table::get_existing_table_id(tx, &data) {
+            // If the table already exists, return the existing table ID
+            Ok(Some(table_id)) => Ok(table_id),
+            Ok(None) => {
+                // If the table does not exist, create a new one if the limit allows it
+                let count = Table::count(tx)
+                    .map_err(|_err| HandlerError::internal("Error creating table"))?;
+                check_limit(count + 1, config.max_tables)?;
+                Table::create(tx, &data)
+                    .map_err(|_err| HandlerError::internal("Error creating table"))
             }
+            Err(_err) => Err(HandlerError::internal("Error creating table")),
         }
-        Err(_err) => {
-            // Respond with an error if there is an issue checking for the existing order
-            Ok(warp::reply::with_status(
-                warp::reply::json(&json!({"error":"Error checking for existing order"})),
-                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ))
-        }
+    });
+
+    match result {
+        Ok(table_id) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({ "id": table_id })),
+            warp::http::StatusCode::CREATED,
+        )
+        .into_response()),
+        // If the limit is reached or creation fails, return the error
+        Err(err) => Ok(err.into_response()),
     }
 }
 
-/// List all orders
-pub async fn list_order_handler(conn: Connection) -> Result<impl warp::Reply, warp::Rejection> {
-    match OrderResponse::list(&conn) {
-        Ok(menus) => Ok(warp::reply::with_status(
-            warp::reply::json(&menus),
-            warp::http::StatusCode::OK,
-        )),
-        Err(_err) => {
-            // If an error occurs while fetching the orders, return an empty array with an internal server error status
-            Ok(warp::reply::with_status(
-                warp::reply::json::<Vec<OrderResponse>>(&vec![]),
-                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-            ))
+/// Create several tables at once, skipping codes that already have a table
+pub async fn create_tables_bulk_handler(
+    mut conn: Connection,
+    config: Arc<Config>,
+    tables: Vec<Table>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if tables.is_empty() {
+        // Return BAD REQUEST if no tables are provided
+        return Ok(HandlerError::new(
+            warp::http::StatusCode::BAD_REQUEST,
+            "Please Add Table Codes",
+        )
+        .into_response());
+    }
+
+    // Create all tables in a single transaction so the result is all-or-nothing
+    let result: Result<_, HandlerError> = run_in_transaction(&mut conn, |tx| {
+        let failed = |_err| HandlerError::internal("Error creating tables");
+        let mut creations = Vec::with_capacity(tables.len());
+        for table in &tables {
+            let (id, created) = match Table::get_existing_table_id(tx, table).map_err(failed)? {
+                // If the table already exists, report its ID without creating it again
+                Some(table_id) => (table_id, false),
+                None => (Table::create(tx, table).map_err(failed)?, true),
+            };
+            creations.push(TableCreation {
+                code: table.code.clone(),
+                id,
+                created,
+            });
         }
+        // Roll everything back if the new tables took the count past MAX_TABLES
+        check_limit(Table::count(tx).map_err(failed)?, config.max_tables)?;
+        Ok(creations)
+    });
+
+    match result {
+        Ok(creations) => Ok(json_reply(&creations, warp::http::StatusCode::OK)),
+        // If the limit is reached or any creation fails, return the error
+        Err(err) => Ok(err.into_response()),
     }
 }
 
-/// Delete a specific order item from an order by table ID
-pub async fn delete_order_item_handler(
+/// Change a table's code
+pub async fn update_table_handler(
     conn: Connection,
     table_id: i64,
-    menu_id: i64,
+    data: Table,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Decrease the item quantity if greater than 1
-    let result = conn.execute(
-        "UPDATE order_items 
-        SET cooking_time = cooking_time - (cooking_time/quantity), quantity = quantity - 1
-        WHERE order_items.order_id IN (
-            SELECT orders.id
-            FROM orders
-            JOIN tables ON orders.table_id = tables.id
-            WHERE tables.id = ?1
-        ) AND order_items.menu_id = ?2 AND order_items.quantity > 1",
-        params![table_id, menu_id],
-    );
-
-    match result {
-        Ok(updated) => {
-            if updated > 0 {
-                // If quantity was greater than 1, update and return success
-                Ok(warp::reply::with_status(
-                    warp::reply::json(&json!({"success": "Menu quantity updated successfully"})),
-                    warp::http::StatusCode::OK,
-                ))
-            } else {
-                // If quantity is 1, delete the order item
-                let delete_result = conn.execute(
-                    "DELETE FROM order_items 
-                    WHERE order_items.order_id IN (
-                        SELECT orders.id
-                        FROM orders
-                        JOIN tables ON orders.table_id = tables.id
-                        WHERE tables.id = ?1
-                    ) AND order_items.menu_id = ?2",
-                    params![table_id, menu_id],
-                );
-
-                match delete_result {
-                    Ok(_) => {
-                        let order_id_result = OrderResponse::get_existing_order_id(&conn, table_id);
-
-                        match order_id_result {
-                            Ok(Some(order_id)) => {
-                                let has_items = OrderResponse::has_items(&conn, order_id);
-
-                                match has_items {
-                                    Ok(false) => {
-                                        // If there are no more items, delete the order as well
-                                        let _ = conn.execute(
-                                            "DELETE from orders WHERE id = ?",
-                                            params![order_id],
-                                        );
-
-                                        Ok(warp::reply::with_status(
-                                            warp::reply::json(
-                                                &json!({"success": "Menu deleted successfully and order deleted"}),
-                                            ),
-                                            warp::http::StatusCode::OK,
-                                        ))
-                                    }
-                                    Ok(true) => {
-                                        // If there are still items, return success without deleting the order
-                                        Ok(warp::reply::with_status(
-                                            warp::reply::json(
-                                                &json!({"success": "Menu deleted successfully"}),
-                                            ),
-                                            warp::http::StatusCode::OK,
-                                        ))
-                                    }
-                                    Err(_err) => {
-                                        // If an error occurs while checking if the order has items, return an error
-                                        Ok(warp::reply::with_status(
-                                            warp::reply::json(
-                                                &json!({"error": "Menu delete failed"}),
-                                            ),
-                                            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                        ))
-                                    }
-                                }
-                            }
-                            _ => {
-                                // If an error occurs while retrieving the order ID, return an error
-                                Ok(warp::reply::with_status(
-                                    warp::reply::json(
-                                        &json!({"error": "Failed to retrieve order ID"}),
-                                    ),
-                                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                                ))
-                            }
-                        }
-                    }
-                    Err(_) => {
-                        // If deleting the order item fails, return an error
-                        Ok(warp::reply::with_status(
-                            warp::reply::json(&json!({"error": "Menu delete failed"})),
-                            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-                        ))
-                    }
-                }
-            }
+    match Table::update(&conn, table_id, &data) {
+        Ok(true) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"id": table_id, "code": data.code})),
+            warp::http::StatusCode::OK,
+        )),
+        // If no row was updated, the table doesn't exist
+        Ok(false) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"error":"Table not found"})),
+            warp::http::StatusCode::NOT_FOUND,
+        )),
+        // If another table already has the code, the UNIQUE constraint refuses the update
+        Err(rusqlite::Error::SqliteFailure(err, _))
+            if err.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"error":"Table code already exists"})),
+                warp::http::StatusCode::CONFLICT,
+            ))
         }
         Err(_err) => {
-            // If updating the quantity fails, return an error
-            eprintln!("Failed to update quantity: {:?}", _err);
+            // If an error occurs while updating the table, return an internal server error status with an error message
+            tracing::error!(table_id, error = %_err, "failed to update table");
             Ok(warp::reply::with_status(
-                warp::reply::json(&json!({"error": "Failed to update quantity"})),
+                warp::reply::json(&json!({"error":"Error updating table"})),
                 warp::http::StatusCode::INTERNAL_SERVER_ERROR,
             ))
         }
     }
 }
 
-/// List all order items for a specific table
-pub async fn list_order_items_for_table_handler(
-    conn: Connection,
+/// Delete a table that has no active order
+pub async fn delete_table_handler(
+    mut conn: Connection,
     table_id: i64,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    match OrderItem::list_order_items(&conn, table_id) {
-        Ok(items) => Ok(warp::reply::with_status(
-            warp::reply::json(&items),
+    // Check for an order and delete in one transaction so no order is placed in between
+    let result = run_in_transaction(&mut conn, |tx| Table::delete(tx, table_id));
+
+    match result {
+        Ok(TableDeletion::Deleted) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"success":"Table deleted successfully"})),
             warp::http::StatusCode::OK,
         )),
+        // If the table doesn't exist, return a not found status
+        Ok(TableDeletion::NotFound) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"error":"Table not found"})),
+            warp::http::StatusCode::NOT_FOUND,
+        )),
+        // If an order still references the table, refuse to delete it
+        Ok(TableDeletion::HasActiveOrder) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"error":"Table has active order"})),
+            warp::http::StatusCode::CONFLICT,
+        )),
         Err(_err) => {
-            // If an error occurs while fetching the order items, return an empty array with an internal server error status
-            eprintln!("{}", _err);
+            // If an error occurs while deleting the table, return an internal server error status with an error message
+            tracing::error!(table_id, error = %_err, "failed to delete table");
             Ok(warp::reply::with_status(
-                warp::reply::json::<Vec<OrderItemResponse>>(&vec![]),
+                warp::reply::json(&json!({"error":"Error deleting table"})),
                 warp::http::StatusCode::INTERNAL_SERVER_ERROR,
             ))
         }
     }
 }
 
-/// Retrieve a specific item from a specific table
-pub async fn get_order_item_for_table_handler(
+// Handlers for admin operations
+
+/// Stream the schema and data of the whole database as SQL, for backups.
+/// The dump is produced on a blocking thread and sent in chunks, so large databases aren't buffered.
+pub async fn dump_sql_handler(config: Arc<Config>) -> Result<impl warp::Reply, warp::Rejection> {
+    let (mut sender, body) = warp::hyper::Body::channel();
+    let db_path = config.db_path.clone();
+    let busy_timeout = config.db_busy_timeout();
+    let runtime = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let conn = match get_db_conn(&db_path, busy_timeout) {
+            Ok(conn) => conn,
+            Err(_err) => {
+                // Abort the body so the client can tell the dump is missing
+                tracing::error!(error = %_err, "failed to open the database for a dump");
+                sender.abort();
+                return;
+            }
+        };
+        let mut buffer = String::new();
+        let mut client_gone = false;
+        let result = dump_sql(&conn, |statement| {
+            buffer.push_str(&statement);
+            if buffer.len() >= DUMP_CHUNK_BYTES {
+                let chunk = std::mem::take(&mut buffer);
+                client_gone = runtime.block_on(sender.send_data(chunk.into())).is_err();
+            }
+            !client_gone
+        });
+        match result {
+            Ok(()) => {
+                if !client_gone && !buffer.is_empty() {
+                    let _ = runtime.block_on(sender.send_data(buffer.into()));
+                }
+            }
+            Err(_err) => {
+                // Abort the body so the client can tell the dump is incomplete
+                tracing::error!(error = %_err, "failed to dump the database");
+                sender.abort();
+            }
+        }
+    });
+
+    Ok(warp::reply::with_header(
+        warp::reply::Response::new(body),
+        "Content-Type",
+        "application/sql; charset=utf-8",
+    ))
+}
+
+// Handlers for Menu operations
+
+/// List all menus, served from the menu cache when it holds a fresh copy
+pub async fn list_menu_handler(
     conn: Connection,
-    table_id: i64,
-    menu_id: i64,
+    menu_cache: Arc<MenuCache>,
+    query: MenuListQuery,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    match OrderItem::get_item(&conn, table_id, menu_id) {
-        Ok(Some(item)) => Ok(warp::reply::with_status(
-            warp::reply::json(&item),
-            warp::http::StatusCode::OK,
-        )),
-        Ok(None) => {
-            // If no item is found, return a NOT FOUND status with an error message
+    // Only the full list is cached; a category filter always reads the database
+    let category = query.category.as_deref();
+    if category.is_none() {
+        if let Some(menus) = menu_cache.get() {
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&menus),
+                warp::http::StatusCode::OK,
+            ));
+        }
+    }
+
+    // Note the generation first, so a write committed during the read keeps this list out of the cache
+    let generation = menu_cache.generation();
+    match Menu::list_by_category(&conn, category) {
+        Ok(menus) => {
+            if category.is_none() {
+                menu_cache.set(generation, &menus);
+            }
             Ok(warp::reply::with_status(
-                warp::reply::json(&json!({"error": "No Item Found"})),
-                warp::http::StatusCode::NOT_FOUND,
+                warp::reply::json(&menus),
+                warp::http::StatusCode::OK,
             ))
         }
         Err(_err) => {
-            // If an error occurs while retrieving the item, return an internal server error status with an error message
-            eprintln!("{}", _err);
+            // If an error occurs while fetching the menus, return an empty array with an internal server error status
             Ok(warp::reply::with_status(
-                warp::reply::json(&json!({"error": "Something went wrong!"})),
+                warp::reply::json::<Vec<MenuResponse>>(&vec![]),
                 warp::http::StatusCode::INTERNAL_SERVER_ERROR,
             ))
         }
     }
 }
 
-// Unit Tests
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use warp::{hyper::Body, Reply};
-
-    // Set up an in-memory test database
-    fn setup_test_db() -> Connection {
-        println!("Initializing the test database...");
-        let conn = Connection::open_in_memory().expect("Failed to create test database");
-        conn.execute("PRAGMA foreign_keys = ON;", [])
-            .expect("Failed to enable foreign key support");
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS tables (id INTEGER PRIMARY KEY, code TEXT NOT NULL UNIQUE)",
-            [],
-        )
-        .expect("Failed to create tables table");
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS menus (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
-            [],
-        )
-        .expect("Failed to create menus table");
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS orders (id INTEGER PRIMARY KEY, table_id INTEGER NOT NULL, FOREIGN KEY (table_id) REFERENCES tables(id), UNIQUE (table_id))",
-            [],
-        )
-        .expect("Failed to create orders table");
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS order_items (id INTEGER PRIMARY KEY, order_id INTEGER NOT NULL, menu_id INTEGER NOT NULL, cooking_time INTEGER NOT NULL, quantity INTEGER NOT NULL default 1, FOREIGN KEY (order_id) REFERENCES orders(id), FOREIGN KEY (menu_id) REFERENCES menus(id))",
-            [],
-        )
-        .expect("Failed to create order_items table");
-        conn
+/// Search the menus by a case-insensitive substring of their name
+pub async fn search_menus_handler(
+    conn: Connection,
+    query: MenuSearchQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let q = query.q.trim();
+    if q.is_empty() {
+        // Reject the request as bad when there is nothing to search for
+        return Err(warp::reject::custom(ApiError::BadRequest(
+            "Search query must not be empty".to_string(),
+        )));
     }
 
-    // Insert static table and menu data into the test database
-    fn setup_static_data(conn: &Connection) {
-        let table_codes = vec!["T-01", "T-02", "T-03"];
-        for code in table_codes {
-            conn.execute("INSERT INTO tables (code) VALUES (?1)", &[code])
-                .expect("Failed to insert table data");
-        }
+    // No match is an empty list rather than an error
+    let menus = Menu::search(&conn, q).map_err(|err| warp::reject::custom(ApiError::from(err)))?;
+    Ok(warp::reply::with_status(
+        warp::reply::json(&menus),
+        warp::http::StatusCode::OK,
+    ))
+}
 
-        let menu_names = vec!["M-01", "M-02", "M-03", "M-04", "M-05"];
-        for name in menu_names {
-            conn.execute("INSERT INTO menus (name) VALUES (?1)", &[name])
-                .expect("Failed to insert menu data");
+/// Create a new menu
+pub async fn create_menu_handler(
+    mut conn: Connection,
+    config: Arc<Config>,
+    menu_cache: Arc<MenuCache>,
+    mut data: Menu,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Menu::validate_name(&data.name, config.menu_name_chars.as_deref()) {
+        Ok(name) => data.name = name,
+        Err(message) => {
+            // Return BAD REQUEST if the name is blank, too long or has disallowed characters
+            return Ok(
+                HandlerError::new(warp::http::StatusCode::BAD_REQUEST, message).into_response(),
+            );
+        }
+    }
+    // A blank category is the same as none
+    data.category = data
+        .category
+        .map(|category| category.trim().to_string())
+        .filter(|category| !category.is_empty());
+    if data.price < 0 {
+        // Return BAD REQUEST for a negative price
+        return Ok(HandlerError::new(
+            warp::http::StatusCode::BAD_REQUEST,
+            "Price must not be negative",
+        )
+        .into_response());
+    }
+    if let Some(cooking_time) = data.cooking_time {
+        let (min, max) = (config.min_cooking_time, config.max_cooking_time);
+        if u64::try_from(cooking_time).map_or(true, |time| time < min || time > max) {
+            // Return BAD REQUEST if the cooking time isn't positive or is out of bounds
+            return Ok(HandlerError::new(
+                warp::http::StatusCode::BAD_REQUEST,
+                format!("Cooking time must be between {} and {}", min, max),
+            )
+            .into_response());
+        }
+    }
+
+    // Count and insert in one transaction so concurrent requests can't exceed MAX_MENUS
+    let result = run_in_transaction(&mut conn, |tx| {
+        match Menu::get_existing_menu_id(tx, &data) {
+            // If the menu already exists, return the existing menu ID
+            Ok(Some(menu_id)) => Ok(menu_id),
+            Ok(None) => {
+                // If the menu does not exist, create a new one if the limit allows it
+                let count = Menu::count(tx)
+                    .map_err(|_err| HandlerError::internal("Error creating Menu"))?;
+                check_limit(count + 1, config.max_menus)?;
+                Menu::create(tx, &data)
+                    .map_err(|_err| HandlerError::internal("Error creating Menu"))
+            }
+            Err(_err) => Err(HandlerError::internal("Error creating Menu")),
+        }
+    });
+
+    match result {
+        Ok(menu_id) => {
+            menu_cache.invalidate();
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({ "id": menu_id })),
+                warp::http::StatusCode::CREATED,
+            )
+            .into_response())
+        }
+        // If the limit is reached or creation fails, return the error
+        Err(err) => Ok(err.into_response()),
+    }
+}
+
+/// Get a single menu by ID
+pub async fn get_menu_handler(
+    conn: Connection,
+    menu_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Menu::get_by_id(&conn, menu_id) {
+        Ok(Some(menu)) => Ok(json_reply(&menu, warp::http::StatusCode::OK)),
+        // If the menu does not exist, reject the request as not found
+        Ok(None) => Err(warp::reject::custom(ApiError::NotFound(
+            "Menu not found".to_string(),
+        ))),
+        // If an error occurs while fetching the menu, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// Rename a menu
+pub async fn update_menu_handler(
+    mut conn: Connection,
+    config: Arc<Config>,
+    menu_cache: Arc<MenuCache>,
+    menu_id: i64,
+    mut data: Menu,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Menu::validate_name(&data.name, config.menu_name_chars.as_deref()) {
+        Ok(name) => data.name = name,
+        Err(message) => {
+            // Return BAD REQUEST if the name is blank, too long or has disallowed characters
+            return Ok(
+                HandlerError::new(warp::http::StatusCode::BAD_REQUEST, message).into_response(),
+            );
+        }
+    }
+
+    // Check the name and rename in one transaction so two menus can't end up with the same name
+    let result = run_in_transaction(&mut conn, |tx| {
+        match Menu::get_existing_menu_id(tx, &data) {
+            // Renaming a menu to its current name changes nothing
+            Ok(Some(existing_id)) if existing_id == menu_id => Ok(()),
+            // If another menu already has the name, refuse the rename
+            Ok(Some(_)) => Err(HandlerError::new(
+                warp::http::StatusCode::CONFLICT,
+                "Menu name already exists",
+            )),
+            Ok(None) => match Menu::update(tx, menu_id, &data) {
+                Ok(true) => Ok(()),
+                // If no row was updated, the menu doesn't exist
+                Ok(false) => Err(HandlerError::new(
+                    warp::http::StatusCode::NOT_FOUND,
+                    "Menu not found",
+                )),
+                Err(_err) => Err(HandlerError::internal("Error updating Menu")),
+            },
+            Err(_err) => Err(HandlerError::internal("Error updating Menu")),
+        }
+    });
+
+    match result {
+        Ok(()) => {
+            menu_cache.invalidate();
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"id": menu_id, "name": data.name})),
+                warp::http::StatusCode::OK,
+            )
+            .into_response())
+        }
+        // If the menu is missing, the name is taken or the update fails, return the error
+        Err(err) => Ok(err.into_response()),
+    }
+}
+
+/// Delete a menu that no order uses
+pub async fn delete_menu_handler(
+    mut conn: Connection,
+    menu_cache: Arc<MenuCache>,
+    menu_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Check for order items and delete in one transaction so no item is added in between
+    let result = run_in_transaction(&mut conn, |tx| Menu::delete(tx, menu_id));
+
+    match result {
+        Ok(MenuDeletion::Deleted) => {
+            menu_cache.invalidate();
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"success":"Menu deleted successfully"})),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        // If the menu doesn't exist, reject the request as not found
+        Ok(MenuDeletion::NotFound) => Err(warp::reject::custom(ApiError::NotFound(
+            "Menu not found".to_string(),
+        ))),
+        // If an order item still references the menu, refuse to delete it
+        Ok(MenuDeletion::InUse) => Err(warp::reject::custom(ApiError::Conflict(
+            "Menu is used in active orders".to_string(),
+        ))),
+        // If an error occurs while deleting the menu, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// Get the IDs for a list of menu names, creating any missing menus
+pub async fn ensure_menus_handler(
+    mut conn: Connection,
+    config: Arc<Config>,
+    menu_cache: Arc<MenuCache>,
+    req_body: EnsureMenusRequestBody,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    if req_body.names.is_empty() || req_body.names.iter().any(|name| name.trim().is_empty()) {
+        // Return BAD REQUEST if no names or blank names are provided
+        return Ok(
+            HandlerError::new(warp::http::StatusCode::BAD_REQUEST, "Please Add Menu Names")
+                .into_response(),
+        );
+    }
+    let names: Result<Vec<String>, String> = req_body
+        .names
+        .iter()
+        .map(|name| Menu::validate_name(name, config.menu_name_chars.as_deref()))
+        .collect();
+    let names = match names {
+        Ok(names) => names,
+        Err(message) => {
+            // Return BAD REQUEST if any name is too long or has disallowed characters
+            return Ok(
+                HandlerError::new(warp::http::StatusCode::BAD_REQUEST, message).into_response(),
+            );
+        }
+    };
+
+    // Look up and create the menus in a single transaction so the result is all-or-nothing
+    let result: Result<_, HandlerError> = run_in_transaction(&mut conn, |tx| {
+        let menu_ids = Menu::ensure(tx, &names).map_err(|_err| {
+            tracing::error!(error = %_err, "failed to ensure menus");
+            HandlerError::internal("Error ensuring Menus")
+        })?;
+        // Roll everything back if the new menus took the count past MAX_MENUS
+        let count =
+            Menu::count(tx).map_err(|_err| HandlerError::internal("Error ensuring Menus"))?;
+        check_limit(count, config.max_menus)?;
+        Ok(menu_ids)
+    });
+
+    match result {
+        Ok(menu_ids) => {
+            menu_cache.invalidate();
+            Ok(
+                warp::reply::with_status(warp::reply::json(&menu_ids), warp::http::StatusCode::OK)
+                    .into_response(),
+            )
+        }
+        // If the limit is reached or any lookup or creation fails, return the error
+        Err(err) => Ok(err.into_response()),
+    }
+}
+
+/// Set the display order of all menus
+pub async fn reorder_menus_handler(
+    mut conn: Connection,
+    menu_cache: Arc<MenuCache>,
+    req_body: ReorderMenusRequestBody,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Check the list and assign positions in one transaction so no menu is created in between
+    let result = run_in_transaction(&mut conn, |tx| {
+        let menu_ids =
+            Menu::ids(tx).map_err(|_err| HandlerError::internal("Error reordering Menus"))?;
+        let mut requested_ids = req_body.menu_ids.clone();
+        requested_ids.sort_unstable();
+        if requested_ids != menu_ids {
+            // Reject lists with missing, unknown or repeated menu IDs
+            return Err(HandlerError::new(
+                warp::http::StatusCode::BAD_REQUEST,
+                "Menu IDs must list every menu exactly once",
+            ));
+        }
+        Menu::reorder(tx, &req_body.menu_ids)
+            .map_err(|_err| HandlerError::internal("Error reordering Menus"))
+    });
+
+    match result {
+        Ok(()) => {
+            menu_cache.invalidate();
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"success":"Menus reordered successfully"})),
+                warp::http::StatusCode::OK,
+            )
+            .into_response())
+        }
+        // If the list is invalid or the update fails, return the error
+        Err(err) => Ok(err.into_response()),
+    }
+}
+
+/// Set the cooking time used for new order items of a menu.
+/// The time must be within the configured `MIN_COOKING_TIME`..=`MAX_COOKING_TIME` bounds.
+pub async fn set_menu_cooking_time_handler(
+    conn: Connection,
+    config: Arc<Config>,
+    menu_id: i64,
+    req_body: MenuCookingTimeRequestBody,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let cooking_time = req_body.cooking_time;
+    let (min, max) = (config.min_cooking_time, config.max_cooking_time);
+    if u64::try_from(cooking_time).map_or(true, |time| time < min || time > max) {
+        // Return BAD REQUEST if the cooking time isn't positive or is out of bounds
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&json!({
+                "error": format!("Cooking time must be between {} and {}", min, max)
+            })),
+            warp::http::StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    match Menu::set_cooking_time(&conn, menu_id, cooking_time) {
+        Ok(true) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({
+                "id": menu_id,
+                "cooking_time": cooking_time,
+                "success": "Menu cooking time updated successfully"
+            })),
+            warp::http::StatusCode::OK,
+        )),
+        // If the menu doesn't exist, return a not found status
+        Ok(false) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"error":"Menu not found"})),
+            warp::http::StatusCode::NOT_FOUND,
+        )),
+        Err(_err) => {
+            // If an error occurs while updating the menu, return an internal server error status with an error message
+            tracing::error!(menu_id, error = %_err, "failed to set menu cooking time");
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"error":"Error updating Menu cooking time"})),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// Rank the menus by the quantity ordered across all current orders
+pub async fn menu_popularity_handler(
+    conn: Connection,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Menu::popularity(&conn) {
+        Ok(ranking) => Ok(json_reply(&ranking, warp::http::StatusCode::OK)),
+        // If an error occurs while aggregating the quantities, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// Get order statistics for a specific menu
+pub async fn get_menu_stats_handler(
+    conn: Connection,
+    menu_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Menu::exists(&conn, menu_id) {
+        Ok(true) => match Menu::stats(&conn, menu_id) {
+            Ok(stats) => Ok(json_reply(&stats, warp::http::StatusCode::OK)),
+            Err(_err) => {
+                // If an error occurs while aggregating the statistics, return an internal server error status with an error message
+                tracing::error!(menu_id, error = %_err, "failed to aggregate menu statistics");
+                Ok(json_reply(
+                    &json!({"error": "Something went wrong!"}),
+                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                ))
+            }
+        },
+        Ok(false) => {
+            // If the menu does not exist, return a NOT FOUND status with an error message
+            Ok(json_reply(
+                &json!({"error": "Menu not found"}),
+                warp::http::StatusCode::NOT_FOUND,
+            ))
+        }
+        Err(_err) => {
+            // If an error occurs while checking for the menu, return an internal server error status with an error message
+            tracing::error!(menu_id, error = %_err, "failed to look up menu");
+            Ok(json_reply(
+                &json!({"error": "Something went wrong!"}),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+// Handlers for Order operations
+
+/// Create a new order.
+/// With an `Idempotency-Key`, a retry within `IDEMPOTENCY_KEY_TTL` gets the original response
+/// back instead of adding the items again; reusing the key for a different request is UNPROCESSABLE ENTITY.
+pub async fn create_order_handler(
+    mut conn: Connection,
+    config: Arc<Config>,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+    idempotency_key: Option<String>,
+    req_body: OrderRequestBody,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let table_id = req_body.table_id;
+    if idempotency_key
+        .as_ref()
+        .is_some_and(|key| key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH)
+    {
+        return Ok(HandlerError::new(
+            warp::http::StatusCode::BAD_REQUEST,
+            format!(
+                "Idempotency-Key must be 1 to {} characters",
+                MAX_IDEMPOTENCY_KEY_LENGTH
+            ),
+        )
+        .into_response());
+    }
+
+    // The request is kept with its key, so the key can't replay a response to another request
+    let request = match serde_json::to_string(&req_body) {
+        Ok(request) => request,
+        Err(_err) => {
+            return Ok(HandlerError::internal("Failed to serialize request").into_response());
+        }
+    };
+
+    // Hold the table's lock so concurrent requests for it don't race on the same order
+    let _table_lock = table_locks.lock(table_id).await;
+    // Create or update the order and all of its items atomically
+    let result = run_in_transaction(&mut conn, |tx| {
+        // A key seen before answers with its stored response; the write lock keeps retries in line
+        if let Some(key) = &idempotency_key {
+            let stored = IdempotentResponse::get(tx, key, config.idempotency_key_ttl_secs)
+                .map_err(|_err| HandlerError::internal("Error checking idempotency key"))?;
+            if let Some(stored) = stored {
+                if stored
+                    .request
+                    .as_ref()
+                    .is_some_and(|stored| *stored != request)
+                {
+                    return Err(HandlerError::new(
+                        warp::http::StatusCode::UNPROCESSABLE_ENTITY,
+                        "Idempotency-Key was already used for a different request",
+                    ));
+                }
+                return Ok((stored, None));
+            }
+        }
+
+        let validation = req_body
+            .validate(tx, config.max_quantity_per_request)
+            .map_err(|_err| HandlerError::internal("Error validating order"))?;
+        if !validation.valid {
+            // Return BAD REQUEST if no menu items are provided or any of them is unknown
+            return Err(HandlerError::new(
+                warp::http::StatusCode::BAD_REQUEST,
+                validation.errors.join("; "),
+            ));
+        }
+
+        let existing_order_id = OrderResponse::get_existing_order_id(tx, table_id)
+            .map_err(|_err| HandlerError::internal("Error checking for existing order"))?;
+        let order_id = match existing_order_id {
+            Some(order_id) => order_id,
+            // If no active order exists, free the table of a closed one and create a new one
+            None => OrderResponse::release_closed_order(tx, table_id)
+                .and_then(|()| OrderResponse::create(tx, table_id))
+                .map_err(|err| HandlerError::internal(format!("Error creating order {}", err)))?,
+        };
+
+        // Each item is added with its whole quantity, raising an existing item's quantity
+        for item in &validation.items {
+            let menu_id = item.menu_id;
+            match OrderItem::get_existing_order_item_id(tx, order_id, menu_id) {
+                Ok(Some(order_item_id)) => {
+                    // If order item exists, update its quantity
+                    OrderItem::add_quantity_of_existing_order_item(
+                        tx,
+                        order_item_id,
+                        item.quantity,
+                    )
+                    .map_err(|err| {
+                        HandlerError::internal(
+                            integrity_violation(&err).unwrap_or("Error updating order Item"),
+                        )
+                    })?;
+                }
+                Ok(None) => {
+                    // Use the menu's calibrated cooking time, or a random one if it has none
+                    let cooking_time = Menu::cooking_time(tx, menu_id)
+                        .map_err(|_err| HandlerError::internal("Error creating order Item"))?
+                        .unwrap_or_else(|| rand::thread_rng().gen_range(5..=15));
+                    // If order item does not exist, create a new one
+                    OrderItem::create(tx, order_id, menu_id, cooking_time, item.quantity).map_err(
+                        |_err| {
+                            tracing::error!(table_id, menu_id, error = %_err, "failed to create order item");
+                            HandlerError::internal(
+                                integrity_violation(&_err).unwrap_or("Error creating order Item"),
+                            )
+                        },
+                    )?;
+                }
+                Err(_err) => {
+                    return Err(HandlerError::internal(
+                        "Error checking for existing order Item",
+                    ));
+                }
+            }
+        }
+
+        // Read the order back so the client sees its current items and totals
+        let order = OrderResponse::get_by_id(tx, order_id)
+            .map_err(|_err| HandlerError::internal("Error loading order"))?
+            .ok_or_else(|| HandlerError::internal("Error loading order"))?;
+        let body = serde_json::to_string(&order)
+            .map_err(|_err| HandlerError::internal("Failed to serialize response"))?;
+        let (response, event) = match existing_order_id {
+            // If the order and all order items were successfully created, return the new order
+            None => (
+                IdempotentResponse {
+                    request: Some(request.clone()),
+                    status: warp::http::StatusCode::CREATED.as_u16(),
+                    location: Some(format!("/orders/{}", order.id)),
+                    body,
+                },
+                OrderEvent::new(OrderEventKind::Created, order_id),
+            ),
+            // If all order items were added to the existing order, return the updated order
+            Some(_) => (
+                IdempotentResponse {
+                    request: Some(request.clone()),
+                    status: warp::http::StatusCode::OK.as_u16(),
+                    location: None,
+                    body,
+                },
+                OrderEvent::new(OrderEventKind::Updated, order_id),
+            ),
+        };
+        // Remember the response in the same transaction, so it's stored exactly when the items are
+        if let Some(key) = &idempotency_key {
+            response
+                .store(tx, key, config.idempotency_key_ttl_secs)
+                .map_err(|_err| HandlerError::internal("Error storing idempotency key"))?;
+        }
+        Ok((response, Some(event)))
+    });
+
+    match result {
+        Ok((response, event)) => {
+            // Replayed responses changed nothing, so only fresh ones are announced
+            if let Some(event) = event {
+                order_events.publish(event);
+            }
+            let status = warp::http::StatusCode::from_u16(response.status)
+                .unwrap_or(warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+            let reply = json_body_reply(response.body, status);
+            match response.location {
+                Some(location) => {
+                    Ok(warp::reply::with_header(reply, "Location", location).into_response())
+                }
+                None => Ok(reply),
+            }
+        }
+        // If any step fails, nothing was written and the error is returned
+        Err(err) => Ok(err.into_response()),
+    }
+}
+
+/// Validate an order request without creating anything, echoing back its normalized form.
+/// Invalid orders still answer 200, with `valid` false and the problems listed in `errors`.
+pub async fn validate_order_handler(
+    conn: Connection,
+    config: Arc<Config>,
+    req_body: OrderRequestBody,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match req_body.validate(&conn, config.max_quantity_per_request) {
+        Ok(validation) => Ok(warp::reply::with_status(
+            warp::reply::json(&validation),
+            warp::http::StatusCode::OK,
+        )),
+        Err(_err) => {
+            // If an error occurs while looking up the menus, return an internal server error status with an error message
+            tracing::error!(error = %_err, "failed to look up menus for order validation");
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"error":"Error validating order"})),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// Copy an order's items into a new order on another table, leaving the original untouched
+pub async fn copy_order_handler(
+    mut conn: Connection,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+    order_id: i64,
+    req_body: CopyOrderRequestBody,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let table_id = req_body.table_id;
+    // Hold the target table's lock so a concurrent order can't claim it mid-copy
+    let _table_lock = table_locks.lock(table_id).await;
+    let result = run_in_transaction(&mut conn, |tx| {
+        let failed = |_err| HandlerError::internal("Error copying order");
+        if !OrderResponse::exists(tx, order_id).map_err(failed)? {
+            return Err(HandlerError::new(
+                warp::http::StatusCode::NOT_FOUND,
+                "Order not found",
+            ));
+        }
+        if !Table::exists(tx, table_id).map_err(failed)? {
+            return Err(HandlerError::new(
+                warp::http::StatusCode::NOT_FOUND,
+                "Table not found",
+            ));
+        }
+        // A table holds a single open order, so the target must be free
+        if OrderResponse::get_existing_order_id(tx, table_id)
+            .map_err(failed)?
+            .is_some()
+        {
+            return Err(HandlerError::new(
+                warp::http::StatusCode::CONFLICT,
+                "Table already has an open order",
+            ));
+        }
+        OrderResponse::release_closed_order(tx, table_id).map_err(failed)?;
+        let new_order_id = OrderResponse::create(tx, table_id).map_err(failed)?;
+        OrderResponse::copy_items(tx, order_id, new_order_id).map_err(failed)?;
+        Ok(new_order_id)
+    });
+
+    match result {
+        Ok(new_order_id) => {
+            order_events.publish(OrderEvent::new(OrderEventKind::Created, new_order_id));
+            Ok(warp::reply::with_status(
+                warp::reply::with_header(
+                    warp::reply::json(
+                        &json!({"id":new_order_id, "success":"Order copied successfully"}),
+                    ),
+                    "Location",
+                    format!("/orders/{}", new_order_id),
+                ),
+                warp::http::StatusCode::CREATED,
+            )
+            .into_response())
+        }
+        // If the source or target is missing, the target is taken or copying fails, return the error
+        Err(err) => Ok(err.into_response()),
+    }
+}
+
+/// Set an order's status to one of `ORDER_STATUSES`
+pub async fn update_order_status_handler(
+    conn: Connection,
+    order_events: Arc<OrderEvents>,
+    order_id: i64,
+    req_body: OrderStatusRequestBody,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = req_body.status.trim();
+    if !ORDER_STATUSES.contains(&status) {
+        // Return BAD REQUEST for a status outside the lifecycle
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"error":"Invalid status"})),
+            warp::http::StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    match OrderResponse::set_status(&conn, order_id, status) {
+        Ok(true) => {
+            order_events.publish(OrderEvent::new(OrderEventKind::Updated, order_id));
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({
+                    "id": order_id,
+                    "status": status,
+                    "success": "Order status updated successfully"
+                })),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        // If the order doesn't exist, return a not found status
+        Ok(false) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"error":"Order not found"})),
+            warp::http::StatusCode::NOT_FOUND,
+        )),
+        Err(_err) => {
+            // If an error occurs while updating the order, return an internal server error status with an error message
+            tracing::error!(order_id, error = %_err, "failed to update order status");
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"error":"Error updating Order status"})),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// List a page of orders, the first `DEFAULT_ORDER_PAGE_SIZE` unless `limit` and `offset` say otherwise
+pub async fn list_order_handler(
+    conn: Connection,
+    query: OrderListQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ORDER_PAGE_SIZE)
+        .min(MAX_ORDER_PAGE_SIZE);
+    let offset = query.offset.unwrap_or(0);
+    match OrderResponse::list_paged(&conn, limit, offset) {
+        Ok(menus) => Ok(warp::reply::with_status(
+            warp::reply::json(&menus),
+            warp::http::StatusCode::OK,
+        )),
+        Err(_err) => {
+            // If an error occurs while fetching the orders, return an empty array with an internal server error status
+            Ok(warp::reply::with_status(
+                warp::reply::json::<Vec<OrderResponse>>(&vec![]),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// Get the receipt of a table's order, with its line items, subtotal, tax and total
+pub async fn order_receipt_handler(
+    conn: Connection,
+    config: Arc<Config>,
+    table_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Receipt::for_table(&conn, table_id, config.tax_rate) {
+        Ok(Some(receipt)) => Ok(json_reply(&receipt, warp::http::StatusCode::OK)),
+        // If the table has no order or it was cancelled, reject the request as not found
+        Ok(None) => Err(warp::reject::custom(ApiError::NotFound(
+            "Order not found".to_string(),
+        ))),
+        // If an error occurs while building the receipt, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// Get a single order by ID
+pub async fn get_order_handler(
+    conn: Connection,
+    order_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match OrderResponse::get_by_id(&conn, order_id) {
+        Ok(Some(order)) => Ok(json_reply(&order, warp::http::StatusCode::OK)),
+        Ok(None) => {
+            // If the order does not exist, return a NOT FOUND status with an error message
+            Ok(json_reply(
+                &json!({"error": "Order not found"}),
+                warp::http::StatusCode::NOT_FOUND,
+            ))
+        }
+        Err(_err) => {
+            // If an error occurs while fetching the order, return an internal server error status with an error message
+            tracing::error!(order_id, error = %_err, "failed to fetch order");
+            Ok(json_reply(
+                &json!({"error": "Something went wrong!"}),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// List every order's precomputed totals, for dashboards polling the whole restaurant
+pub async fn list_order_summaries_handler(
+    conn: Connection,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match OrderSummary::list(&conn) {
+        Ok(summaries) => Ok(json_reply(&summaries, warp::http::StatusCode::OK)),
+        Err(_err) => {
+            // If an error occurs while fetching the summaries, return an empty array with an internal server error status
+            tracing::error!(error = %_err, "failed to list order summaries");
+            Ok(json_reply(
+                &Vec::<OrderSummary>::new(),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// Get the whole restaurant state: every table, menu and order
+pub async fn get_state_handler(conn: Connection) -> Result<impl warp::Reply, warp::Rejection> {
+    match get_current_state(&conn) {
+        Ok(state) => Ok(json_reply(&state, warp::http::StatusCode::OK)),
+        Err(_err) => {
+            // If an error occurs while loading the state, return an internal server error status with an error message
+            tracing::error!(error = %_err, "failed to load state");
+            Ok(json_reply(
+                &json!({"error": "Failed to load state"}),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// List the largest open orders, largest first
+pub async fn list_largest_orders_handler(
+    conn: Connection,
+    query: LargestOrdersQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let limit = query.limit.unwrap_or(5);
+    if !(1..=100).contains(&limit) {
+        // Return BAD REQUEST if the limit is out of range
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"error":"Limit must be between 1 and 100"})),
+            warp::http::StatusCode::BAD_REQUEST,
+        ));
+    }
+    let by = query.by.unwrap_or(OrderSizeMeasure::Quantity);
+
+    match OrderResponse::list_largest(&conn, by, limit) {
+        Ok(orders) => Ok(warp::reply::with_status(
+            warp::reply::json(&orders),
+            warp::http::StatusCode::OK,
+        )),
+        Err(_err) => {
+            // If an error occurs while fetching the orders, return an internal server error status with an error message
+            tracing::error!(error = %_err, "failed to list largest orders");
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"error": "Something went wrong!"})),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// List all order items for a specific order
+pub async fn list_order_items_for_order_handler(
+    conn: Connection,
+    order_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match OrderResponse::exists(&conn, order_id) {
+        Ok(true) => match OrderItem::list_all_order_items(&conn, order_id) {
+            Ok(items) => Ok(warp::reply::with_status(
+                warp::reply::json(&items),
+                warp::http::StatusCode::OK,
+            )),
+            Err(_err) => {
+                // If an error occurs while fetching the order items, return an internal server error status with an error message
+                tracing::error!(order_id, error = %_err, "failed to fetch order items");
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&json!({"error": "Something went wrong!"})),
+                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                ))
+            }
+        },
+        Ok(false) => {
+            // If the order does not exist, return a NOT FOUND status with an error message
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"error": "Order not found"})),
+                warp::http::StatusCode::NOT_FOUND,
+            ))
+        }
+        Err(_err) => {
+            // If an error occurs while checking for the order, return an internal server error status with an error message
+            tracing::error!(order_id, error = %_err, "failed to look up order");
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"error": "Something went wrong!"})),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// Delete a specific order item from an order by table ID
+pub async fn delete_order_item_handler(
+    mut conn: Connection,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+    table_id: i64,
+    menu_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Hold the table's lock so concurrent requests for it don't race on the same order
+    let _table_lock = table_locks.lock(table_id).await;
+
+    // Update or delete the item and drop the emptied order atomically
+    let result = run_in_transaction(&mut conn, |tx| -> Result<_, ApiError> {
+        let order_id = OrderResponse::get_existing_order_id(tx, table_id)?;
+        let event = |kind| order_id.map(|order_id| OrderEvent::new(kind, order_id));
+        // Decrease the item quantity if greater than 1
+        let updated = tx
+            .execute(
+                &format!(
+                    "UPDATE order_items 
+                    SET cooking_time = unit_cooking_time * (quantity - 1), quantity = quantity - 1
+                    WHERE order_items.order_id IN (
+                        SELECT orders.id
+                        FROM orders
+                        JOIN tables ON orders.table_id = tables.id
+                        WHERE tables.id = ?1 AND {}
+                    ) AND order_items.menu_id = ?2 AND order_items.quantity > 1",
+                    ACTIVE_ORDER_FILTER
+                ),
+                params![table_id, menu_id],
+            )
+            .map_err(|_err| {
+                tracing::error!(table_id, menu_id, error = %_err, "failed to update item quantity");
+                ApiError::Internal
+            })?;
+        if updated > 0 {
+            return Ok((
+                "Menu quantity updated successfully",
+                event(OrderEventKind::Updated),
+            ));
+        }
+
+        // If quantity is 1, delete the order item
+        let deleted = tx.execute(
+            &format!(
+                "DELETE FROM order_items 
+                WHERE order_items.order_id IN (
+                    SELECT orders.id
+                    FROM orders
+                    JOIN tables ON orders.table_id = tables.id
+                    WHERE tables.id = ?1 AND {}
+                ) AND order_items.menu_id = ?2",
+                ACTIVE_ORDER_FILTER
+            ),
+            params![table_id, menu_id],
+        )?;
+        if deleted == 0 {
+            // If the item (or its whole order) is already gone, there is nothing left to do
+            return Ok(("Menu already deleted", None));
+        }
+
+        // An item was deleted, so the table has an order
+        let order_id = order_id.ok_or(ApiError::Internal)?;
+        let has_items = OrderResponse::has_items(tx, order_id)?;
+        if has_items {
+            return Ok((
+                "Menu deleted successfully",
+                Some(OrderEvent::new(OrderEventKind::Updated, order_id)),
+            ));
+        }
+
+        // If there are no more items, delete the order as well
+        tx.execute("DELETE from orders WHERE id = ?", params![order_id])?;
+        Ok((
+            "Menu deleted successfully and order deleted",
+            Some(OrderEvent::new(OrderEventKind::Deleted, order_id)),
+        ))
+    });
+
+    match result {
+        Ok((message, event)) => {
+            if let Some(event) = event {
+                order_events.publish(event);
+            }
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({ "success": message })),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        // If any step fails, nothing was written and the request is rejected with the error
+        Err(err) => Err(warp::reject::custom(err)),
+    }
+}
+
+/// Clear a table's active order, deleting all of its items and then the order itself
+pub async fn clear_order_handler(
+    mut conn: Connection,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+    table_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Hold the table's lock so no items are added to the order while it is cleared
+    let _table_lock = table_locks.lock(table_id).await;
+
+    // Delete the items and the order atomically
+    let result = run_in_transaction(&mut conn, |tx| -> Result<i64, ApiError> {
+        let order_id = OrderResponse::get_existing_order_id(tx, table_id)?
+            .ok_or_else(|| ApiError::NotFound("No active order for this table".to_string()))?;
+        OrderResponse::delete(tx, order_id)?;
+        Ok(order_id)
+    });
+
+    match result {
+        Ok(order_id) => {
+            order_events.publish(OrderEvent::new(OrderEventKind::Deleted, order_id));
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"success":"Order cleared"})),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        // If the table has no active order or any step fails, nothing was written and the request is rejected
+        Err(err) => Err(warp::reject::custom(err)),
+    }
+}
+
+/// Open a websocket for the kitchen: the current orders are sent first, then an event per change
+pub async fn ws_orders_handler(
+    ws: warp::ws::Ws,
+    conn: Connection,
+    order_events: Arc<OrderEvents>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Subscribe before reading the orders so no change made in between is missed
+    let events = order_events.subscribe();
+    let orders =
+        OrderResponse::list(&conn).map_err(|err| warp::reject::custom(ApiError::from(err)))?;
+    let snapshot = serde_json::to_string(&orders).map_err(|_err| {
+        tracing::error!(error = %_err, "failed to serialize orders");
+        warp::reject::custom(ApiError::Internal)
+    })?;
+    // Return the connection to the pool rather than holding it for the socket's lifetime
+    drop(conn);
+    Ok(ws.on_upgrade(move |socket| push_order_events(socket, snapshot, events)))
+}
+
+/// Cancel an order by its ID, deleting it along with its items
+pub async fn delete_order_handler(
+    mut conn: Connection,
+    order_events: Arc<OrderEvents>,
+    order_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // Delete the items and the order atomically
+    let result = run_in_transaction(&mut conn, |tx| OrderResponse::delete(tx, order_id));
+
+    match result {
+        Ok(true) => {
+            order_events.publish(OrderEvent::new(OrderEventKind::Deleted, order_id));
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({"success":"Order deleted"})),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        // If the order doesn't exist, reject the request as not found
+        Ok(false) => Err(warp::reject::custom(ApiError::NotFound(
+            "Order not found".to_string(),
+        ))),
+        // If an error occurs while deleting the order, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// Set the quantity of an item in a table's order; a quantity of 0 deletes the item,
+/// and the order too when it has no items left
+pub async fn set_item_quantity_handler(
+    mut conn: Connection,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+    table_id: i64,
+    menu_id: i64,
+    req_body: ItemQuantityRequestBody,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let quantity = req_body.quantity;
+    if quantity < 0 {
+        // Reject the request as bad for a negative quantity
+        return Err(warp::reject::custom(ApiError::BadRequest(
+            "Quantity must not be negative".to_string(),
+        )));
+    }
+
+    // Hold the table's lock so concurrent requests for it don't race on the same order
+    let _table_lock = table_locks.lock(table_id).await;
+
+    // Change the item and drop the emptied order atomically
+    let result = run_in_transaction(&mut conn, |tx| -> Result<(&str, OrderEvent), ApiError> {
+        let not_found = || ApiError::NotFound("No Item Found".to_string());
+        let order_id = OrderResponse::get_existing_order_id(tx, table_id)?.ok_or_else(not_found)?;
+        let updated = OrderEvent::new(OrderEventKind::Updated, order_id);
+        if quantity > 0 {
+            return if OrderItem::set_quantity(tx, order_id, menu_id, quantity)? {
+                Ok(("Item quantity updated", updated))
+            } else {
+                Err(not_found())
+            };
+        }
+
+        // A quantity of 0 deletes the item
+        let deleted = tx.execute(
+            "DELETE FROM order_items WHERE order_id = ?1 AND menu_id = ?2",
+            params![order_id, menu_id],
+        )?;
+        if deleted == 0 {
+            return Err(not_found());
+        }
+        if OrderResponse::has_items(tx, order_id)? {
+            return Ok(("Item deleted", updated));
+        }
+        // If there are no more items, delete the order as well
+        tx.execute("DELETE FROM orders WHERE id = ?1", params![order_id])?;
+        Ok((
+            "Item deleted and order deleted",
+            OrderEvent::new(OrderEventKind::Deleted, order_id),
+        ))
+    });
+
+    match result {
+        Ok((message, event)) => {
+            order_events.publish(event);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&json!({ "success": message })),
+                warp::http::StatusCode::OK,
+            ))
+        }
+        // If the item is missing or any step fails, nothing was written and the request is rejected
+        Err(err) => Err(warp::reject::custom(err)),
+    }
+}
+
+/// Mark an item of a table's order as served, so waiters can see it was delivered
+pub async fn mark_item_served_handler(
+    conn: Connection,
+    table_id: i64,
+    menu_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match OrderItem::mark_served(&conn, table_id, menu_id) {
+        Ok(true) => Ok(warp::reply::with_status(
+            warp::reply::json(&json!({"success": "Item marked served"})),
+            warp::http::StatusCode::OK,
+        )),
+        // If the table's order has no item for the menu, reject the request as not found
+        Ok(false) => Err(warp::reject::custom(ApiError::NotFound(
+            "No Item Found".to_string(),
+        ))),
+        // If an error occurs while updating the item, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// List all order items for a specific table, empty when the table has no order
+pub async fn list_order_items_for_table_handler(
+    conn: Connection,
+    table_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match Table::exists(&conn, table_id) {
+        Ok(true) => match OrderItem::list_order_items(&conn, table_id) {
+            Ok(items) => Ok(json_reply(&items, warp::http::StatusCode::OK)),
+            Err(_err) => {
+                // If an error occurs while fetching the order items, return an empty array with an internal server error status
+                tracing::error!(table_id, error = %_err, "failed to fetch order items");
+                Ok(json_reply(
+                    &Vec::<OrderItemResponse>::new(),
+                    warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                ))
+            }
+        },
+        Ok(false) => {
+            // If the table does not exist, return a NOT FOUND status with an error message
+            Ok(json_reply(
+                &json!({"error": "Table not found"}),
+                warp::http::StatusCode::NOT_FOUND,
+            ))
+        }
+        Err(_err) => {
+            // If an error occurs while checking for the table, return an empty array with an internal server error status
+            tracing::error!(table_id, error = %_err, "failed to look up table");
+            Ok(json_reply(
+                &Vec::<OrderItemResponse>::new(),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+            ))
+        }
+    }
+}
+
+/// List the order items of several tables in one call, keyed by table ID
+pub async fn list_order_items_for_tables_handler(
+    conn: Connection,
+    query: TableIdsQuery,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let table_ids: Result<BTreeSet<i64>, _> = query
+        .ids
+        .split(',')
+        .map(|table_id| table_id.trim().parse::<i64>())
+        .collect();
+    let table_ids = match table_ids {
+        Ok(table_ids) => table_ids,
+        Err(_err) => {
+            // Reject the request as bad if any ID is not a number
+            return Err(warp::reject::custom(ApiError::BadRequest(
+                "Table IDs must be a comma-separated list of numbers".to_string(),
+            )));
+        }
+    };
+    if table_ids.len() > MAX_TABLE_IDS {
+        // Reject the request as bad if too many tables are requested at once
+        return Err(warp::reject::custom(ApiError::BadRequest(format!(
+            "At most {} table IDs can be requested",
+            MAX_TABLE_IDS
+        ))));
+    }
+
+    match OrderItem::list_order_items_for_tables(&conn, &table_ids) {
+        Ok(items) => Ok(warp::reply::with_status(
+            warp::reply::json(&items),
+            warp::http::StatusCode::OK,
+        )),
+        // If an error occurs while fetching the order items, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// Retrieve a specific item from a specific table
+pub async fn get_order_item_for_table_handler(
+    conn: Connection,
+    table_id: i64,
+    menu_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match OrderItem::get_item(&conn, table_id, menu_id) {
+        Ok(Some(item)) => Ok(warp::reply::with_status(
+            warp::reply::json(&item),
+            warp::http::StatusCode::OK,
+        )),
+        // If no item is found, reject the request as not found
+        Ok(None) => Err(warp::reject::custom(ApiError::NotFound(
+            "No Item Found".to_string(),
+        ))),
+        // If an error occurs while retrieving the item, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+/// Retrieve a specific item from a specific order
+pub async fn get_order_item_for_order_handler(
+    conn: Connection,
+    order_id: i64,
+    menu_id: i64,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    match OrderItem::get_by_order(&conn, order_id, menu_id) {
+        Ok(Some(item)) => Ok(warp::reply::with_status(
+            warp::reply::json(&item),
+            warp::http::StatusCode::OK,
+        )),
+        // If no item is found, reject the request as not found
+        Ok(None) => Err(warp::reject::custom(ApiError::NotFound(
+            "No Item Found".to_string(),
+        ))),
+        // If an error occurs while retrieving the item, reject the request as an internal error
+        Err(err) => Err(warp::reject::custom(ApiError::from(err))),
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::models::MAX_MENU_NAME_LENGTH;
+    use warp::hyper::Body;
+
+    // Set up an in-memory test database with the application schema,
+    // as the only connection of its own pool so it can be handed to handlers
+    pub(crate) fn setup_test_db() -> Connection {
+        println!("Initializing the test database...");
+        let pool = r2d2::Pool::builder()
+            .max_size(1)
+            .build(r2d2_sqlite::SqliteConnectionManager::memory())
+            .expect("Failed to create test database");
+        let conn = pool.get().expect("Failed to create test database");
+        crate::db::initialize_schema(&conn);
+        conn
+    }
+
+    // Insert static table and menu data into the test database
+    pub(crate) fn setup_static_data(conn: &rusqlite::Connection) {
+        let table_codes = vec!["T-01", "T-02", "T-03"];
+        for code in table_codes {
+            conn.execute("INSERT INTO tables (code) VALUES (?1)", [code])
+                .expect("Failed to insert table data");
+        }
+
+        let menu_names = vec!["M-01", "M-02", "M-03", "M-04", "M-05"];
+        for name in menu_names {
+            conn.execute("INSERT INTO menus (name) VALUES (?1)", [name])
+                .expect("Failed to insert menu data");
+        }
+    }
+
+    // Convert a warp Response to a serde JSON Value
+    pub(crate) async fn convert_response_to_json(
+        resp: warp::http::Response<Body>,
+    ) -> serde_json::Value {
+        let body_bytes = warp::hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        let body_string = String::from_utf8_lossy(&body_bytes);
+        serde_json::from_str(&body_string).unwrap()
+    }
+
+    // Test Case: Server info
+    #[tokio::test]
+    async fn test_info_handler() {
+        let config = Config {
+            restaurant_name: "Chez Rust".to_string(),
+            ..Config::default()
+        };
+        let started_at = Instant::now() - std::time::Duration::from_secs(90);
+        let result = info_handler(Arc::new(config), started_at).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["name"].as_str(), Some("Chez Rust"));
+                assert_eq!(
+                    json_data["version"].as_str(),
+                    Some(env!("CARGO_PKG_VERSION"))
+                );
+                assert!(json_data["uptime_seconds"].as_u64() >= Some(90));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Menu Creation
+    #[tokio::test]
+    async fn test_create_menu_handler() {
+        let conn = setup_test_db();
+        let menu = Menu {
+            id: 0,
+            name: "Menu-01".to_string(),
+            price: 0,
+            category: None,
+            cooking_time: None,
+        };
+        let result = create_menu_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::new(MenuCache::default()),
+            menu,
+        )
+        .await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["id"].as_i64(), Some(1));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Menu names are trimmed before they are stored
+    #[tokio::test]
+    async fn test_create_menu_handler_trims_name() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let menu = Menu {
+            id: 0,
+            name: "  M-02 ".to_string(),
+            price: 0,
+            category: None,
+            cooking_time: None,
+        };
+        let result = create_menu_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::new(MenuCache::default()),
+            menu,
+        )
+        .await;
+        // Expecting the trimmed name to match the existing menu 2
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["id"].as_i64(), Some(2));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Menu creation fails with blank, overlong or disallowed names
+    #[tokio::test]
+    async fn test_create_menu_handler_invalid_names() {
+        let config = Arc::new(Config {
+            menu_name_chars: Some("-'".to_string()),
+            ..Config::default()
+        });
+        let cases = [
+            ("   ", "Please Add Menu Name".to_string()),
+            (
+                &*"x".repeat(MAX_MENU_NAME_LENGTH + 1),
+                format!(
+                    "Menu name must be at most {} characters",
+                    MAX_MENU_NAME_LENGTH
+                ),
+            ),
+            (
+                "Fish & Chips!!",
+                "Menu name contains disallowed characters: \"&!\"".to_string(),
+            ),
+            (
+                "Soup\u{7}\tdu jour",
+                "Menu name contains disallowed characters: \"\\u{7}\\t\"".to_string(),
+            ),
+        ];
+        for (name, error) in cases {
+            let menu = Menu {
+                id: 0,
+                name: name.to_string(),
+                price: 0,
+                category: None,
+                cooking_time: None,
+            };
+            let result = create_menu_handler(
+                setup_test_db(),
+                config.clone(),
+                Arc::new(MenuCache::default()),
+                menu,
+            )
+            .await;
+            // Expecting each name to be rejected with a message naming the problem
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+                    let json_data = convert_response_to_json(resp).await;
+                    assert_eq!(json_data["error"].as_str(), Some(error.as_str()));
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
+
+    // Test Case: Without a configured character class any characters are accepted
+    #[tokio::test]
+    async fn test_create_menu_handler_default_allows_any_characters() {
+        let menu = Menu {
+            id: 0,
+            name: "Fish & Chips!!".to_string(),
+            price: 0,
+            category: None,
+            cooking_time: None,
+        };
+        let result = create_menu_handler(
+            setup_test_db(),
+            Arc::new(Config::default()),
+            Arc::new(MenuCache::default()),
+            menu,
+        )
+        .await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Table Creation
+    #[tokio::test]
+    async fn test_create_table_handler() {
+        let conn = setup_test_db();
+        let table = Table {
+            id: 0,
+            code: "Table-01".to_string(),
+        };
+        let result = create_table_handler(conn, Arc::new(Config::default()), table).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["id"].as_i64(), Some(1));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Table creation fails once MAX_TABLES is reached
+    #[tokio::test]
+    async fn test_create_table_handler_limit_reached() {
+        let config = Arc::new(Config {
+            max_tables: 3,
+            ..Config::default()
+        });
+        for (code, status) in [
+            ("T-01", warp::http::StatusCode::CREATED),
+            ("T-04", warp::http::StatusCode::CONFLICT),
+        ] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            let table = Table {
+                id: 0,
+                code: code.to_string(),
+            };
+            let result = create_table_handler(conn, config.clone(), table).await;
+            // Expecting existing tables to be returned and new ones rejected at the cap
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), status);
+                    if status == warp::http::StatusCode::CONFLICT {
+                        let json_data = convert_response_to_json(resp).await;
+                        assert_eq!(json_data["error"].as_str(), Some("Resource limit reached"));
+                    }
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
+
+    // Test Case: Bulk table creation creates new codes and skips existing ones
+    #[tokio::test]
+    async fn test_create_tables_bulk_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let tables = ["T-04", "T-05", "T-02", "T-06", "T-07"]
+            .iter()
+            .map(|code| Table {
+                id: 0,
+                code: code.to_string(),
+            })
+            .collect();
+        let result = create_tables_bulk_handler(conn, Arc::new(Config::default()), tables).await;
+        // Expecting T-02 to keep its ID and the others to be created after the static tables
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                let creations: Vec<TableCreation> =
+                    serde_json::from_value(json_data).expect("Expected a list of tables");
+                let created: Vec<(&str, i64, bool)> = creations
+                    .iter()
+                    .map(|table| (table.code.as_str(), table.id, table.created))
+                    .collect();
+                assert_eq!(
+                    created,
+                    vec![
+                        ("T-04", 4, true),
+                        ("T-05", 5, true),
+                        ("T-02", 2, false),
+                        ("T-06", 6, true),
+                        ("T-07", 7, true),
+                    ]
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Bulk table creation creates nothing when it would exceed MAX_TABLES
+    #[tokio::test]
+    async fn test_create_tables_bulk_handler_limit_reached() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let config = Arc::new(Config {
+            max_tables: 4,
+            ..Config::default()
+        });
+        let tables = ["T-04", "T-05"]
+            .iter()
+            .map(|code| Table {
+                id: 0,
+                code: code.to_string(),
+            })
+            .collect();
+        let result = create_tables_bulk_handler(conn, config, tables).await;
+        // Expecting the second new table to take the count past the cap
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CONFLICT);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Resource limit reached"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Menu creation fails once MAX_MENUS is reached
+    #[tokio::test]
+    async fn test_create_menu_handler_limit_reached() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let config = Arc::new(Config {
+            max_menus: 5,
+            ..Config::default()
+        });
+        let menu = Menu {
+            id: 0,
+            name: "M-06".to_string(),
+            price: 0,
+            category: None,
+            cooking_time: None,
+        };
+        let result = create_menu_handler(conn, config, Arc::new(MenuCache::default()), menu).await;
+        // Expecting the sixth menu to be rejected
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CONFLICT);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Resource limit reached"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Ensuring menus past MAX_MENUS is rejected
+    #[tokio::test]
+    async fn test_ensure_menus_handler_limit_reached() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let config = Arc::new(Config {
+            max_menus: 6,
+            ..Config::default()
+        });
+        let req_body = EnsureMenusRequestBody {
+            names: vec!["M-06".to_string(), "M-07".to_string()],
+        };
+        let result =
+            ensure_menus_handler(conn, config, Arc::new(MenuCache::default()), req_body).await;
+        // Expecting the two new menus to exceed the cap of six
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CONFLICT);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Resource limit reached"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Tables are listed by the cooking time of their open order in either direction
+    #[tokio::test]
+    async fn test_list_tables_by_load_handler() {
+        // Table 1 has 20 minutes of cooking, table 2 has 5 and table 3 has no order
+        let seeded_db = || {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            for (table_id, cooking_times) in [(1, vec![8, 12]), (2, vec![5])] {
+                conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                    .expect("Order creation failed");
+                let order_id = conn.last_insert_rowid();
+                for (menu_id, cooking_time) in cooking_times.into_iter().enumerate() {
+                    conn.execute(
+                        "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                        [order_id, menu_id as i64 + 1, cooking_time],
+                    )
+                    .expect("OrderItems creation failed");
+                }
+            }
+            conn
+        };
+
+        for (direction, expected) in [
+            (None, [("T-03", 0), ("T-02", 5), ("T-01", 20)]),
+            (
+                Some(SortDirection::Desc),
+                [("T-01", 20), ("T-02", 5), ("T-03", 0)],
+            ),
+        ] {
+            let result =
+                list_tables_by_load_handler(seeded_db(), TableLoadQuery { direction }).await;
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                    let json_data = convert_response_to_json(resp).await;
+                    let loads: Vec<_> = json_data
+                        .as_array()
+                        .expect("Expected a list of tables")
+                        .iter()
+                        .map(|table| {
+                            (
+                                table["code"].as_str().unwrap().to_string(),
+                                table["total_cooking_time"].as_i64().unwrap(),
+                            )
+                        })
+                        .collect();
+                    let expected: Vec<_> = expected
+                        .iter()
+                        .map(|(code, load)| (code.to_string(), *load))
+                        .collect();
+                    assert_eq!(loads, expected);
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
+
+    // Test Case: The kitchen load sums each table's open order, most loaded first, and leaves out idle tables
+    #[tokio::test]
+    async fn test_kitchen_load_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        // T-01 needs 5 minutes, T-02 needs 12 + 8 and T-03 has a served order that no longer counts
+        for (table_id, status, cooking_times) in [
+            (1, "pending", vec![5]),
+            (2, "cooking", vec![12, 8]),
+            (3, "served", vec![30]),
+        ] {
+            conn.execute(
+                "INSERT INTO orders (table_id, status) VALUES (?1, ?2)",
+                params![table_id, status],
+            )
+            .expect("Order creation failed");
+            let order_id = conn.last_insert_rowid();
+            for (menu_id, cooking_time) in cooking_times.into_iter().enumerate() {
+                conn.execute(
+                    "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                    params![order_id, menu_id as i64 + 1, cooking_time],
+                )
+                .expect("OrderItems creation failed");
+            }
+        }
+
+        let result = kitchen_load_handler(conn).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data,
+                    serde_json::json!([
+                        {"table_id": 2, "table_code": "T-02", "total_cooking_time": 20},
+                        {"table_id": 1, "table_code": "T-01", "total_cooking_time": 5},
+                    ])
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Only tables with an order are listed as occupied, with the order's total cooking time
+    #[tokio::test]
+    async fn test_occupied_tables_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        // T-01 and T-03 have orders while T-02 is free
+        for (table_id, cooking_times) in [(1, vec![7, 3]), (3, vec![15])] {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+            let order_id = conn.last_insert_rowid();
+            for (menu_id, cooking_time) in cooking_times.into_iter().enumerate() {
+                conn.execute(
+                    "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                    [order_id, menu_id as i64 + 1, cooking_time],
+                )
+                .expect("OrderItems creation failed");
+            }
+        }
+
+        let result = occupied_tables_handler(conn).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data,
+                    serde_json::json!([
+                        {"table_id": 1, "code": "T-01", "total_cooking_time": 10},
+                        {"table_id": 3, "code": "T-03", "total_cooking_time": 15},
+                    ])
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Menus are ranked by the quantity ordered across all orders, and unordered menus are left out
+    #[tokio::test]
+    async fn test_menu_popularity_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        // M-03 is ordered 4 + 3 times, M-01 5 times and M-02 twice; M-04 and M-05 never
+        for (table_id, items) in [(1, vec![(3, 4), (1, 5)]), (2, vec![(3, 3), (2, 2)])] {
+            let order_id = OrderResponse::create(&conn, table_id).expect("Order creation failed");
+            for (menu_id, quantity) in items {
+                OrderItem::create(&conn, order_id, menu_id, 5, quantity)
+                    .expect("OrderItems creation failed");
+            }
+        }
+
+        let result = menu_popularity_handler(conn).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data,
+                    serde_json::json!([
+                        {"menu_id": 3, "menu_name": "M-03", "total_quantity": 7},
+                        {"menu_id": 1, "menu_name": "M-01", "total_quantity": 5},
+                        {"menu_id": 2, "menu_name": "M-02", "total_quantity": 2},
+                    ])
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: A table without an order is deleted
+    #[tokio::test]
+    async fn test_delete_table_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = delete_table_handler(conn, 2).await;
+        // Expecting the table to be deleted
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data["success"].as_str(),
+                    Some("Table deleted successfully")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+
+        // A missing table is reported as not found
+        let conn = setup_test_db();
+        let result = delete_table_handler(conn, 2).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Changing a table's code renames it, unless it is missing or the code is taken
+    #[tokio::test]
+    async fn test_update_table_handler() {
+        for (table_id, code, status) in [
+            (2, "T-22", warp::http::StatusCode::OK),
+            (42, "T-42", warp::http::StatusCode::NOT_FOUND),
+            (2, "T-03", warp::http::StatusCode::CONFLICT),
+        ] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            let table = Table {
+                id: 0,
+                code: code.to_string(),
+            };
+            let result = update_table_handler(conn, table_id, table).await;
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), status, "{}", code);
+                    let json_data = convert_response_to_json(resp).await;
+                    match status {
+                        warp::http::StatusCode::OK => {
+                            assert_eq!(json_data, json!({"id": 2, "code": "T-22"}));
+                        }
+                        warp::http::StatusCode::NOT_FOUND => {
+                            assert_eq!(json_data["error"].as_str(), Some("Table not found"));
+                        }
+                        _ => {
+                            assert_eq!(
+                                json_data["error"].as_str(),
+                                Some("Table code already exists")
+                            );
+                        }
+                    }
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
+
+    // Test Case: A table with an active order can't be deleted
+    #[tokio::test]
+    async fn test_delete_table_handler_active_order() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+        let result = delete_table_handler(conn, 1).await;
+        // Expecting a conflict since table 1 has an order
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CONFLICT);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Table has active order"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: The health check reports ok while the database answers
+    #[tokio::test]
+    async fn test_health_handler() {
+        let conn = setup_test_db();
+        let result = health_handler(conn).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data, json!({"status": "ok"}));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Deleting a menu no order uses
+    #[tokio::test]
+    async fn test_delete_menu_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = delete_menu_handler(conn, Arc::default(), 3).await;
+        // Expecting the menu to be deleted
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data["success"].as_str(),
+                    Some("Menu deleted successfully")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+
+        // A missing menu is reported as not found
+        let conn = setup_test_db();
+        let result = delete_menu_handler(conn, Arc::default(), 3).await;
+        match result {
+            Ok(_) => {
+                panic!("Expected the request to be rejected");
+            }
+            Err(rejection) => {
+                assert_eq!(
+                    rejection.find::<ApiError>(),
+                    Some(&ApiError::NotFound("Menu not found".to_string()))
+                );
+            }
+        }
+    }
+
+    // Test Case: A menu used by an order item can't be deleted
+    #[tokio::test]
+    async fn test_delete_menu_handler_in_use() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (1, 2, 10)",
+            [],
+        )
+        .expect("Order item creation failed");
+        let result = delete_menu_handler(conn, Arc::default(), 2).await;
+        // Expecting a conflict since menu 2 is on order 1
+        match result {
+            Ok(_) => {
+                panic!("Expected the request to be rejected");
+            }
+            Err(rejection) => {
+                assert_eq!(
+                    rejection.find::<ApiError>(),
+                    Some(&ApiError::Conflict(
+                        "Menu is used in active orders".to_string()
+                    ))
+                );
+            }
+        }
+    }
+
+    // Test Case: Renaming a menu updates its name
+    #[tokio::test]
+    async fn test_update_menu_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let menu = Menu {
+            id: 0,
+            name: " Soup of the Day ".to_string(),
+            price: 0,
+            category: None,
+            cooking_time: None,
+        };
+        let result =
+            update_menu_handler(conn, Arc::new(Config::default()), Arc::default(), 2, menu).await;
+        // Expecting the trimmed new name for menu 2
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["id"].as_i64(), Some(2));
+                assert_eq!(json_data["name"].as_str(), Some("Soup of the Day"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Renaming fails for a missing menu or a name another menu has
+    #[tokio::test]
+    async fn test_update_menu_handler_wrong_data() {
+        for (menu_id, name, status, error) in [
+            (
+                99,
+                "M-99",
+                warp::http::StatusCode::NOT_FOUND,
+                "Menu not found",
+            ),
+            (
+                2,
+                "M-01",
+                warp::http::StatusCode::CONFLICT,
+                "Menu name already exists",
+            ),
+        ] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            let menu = Menu {
+                id: 0,
+                name: name.to_string(),
+                price: 0,
+                category: None,
+                cooking_time: None,
+            };
+            let result = update_menu_handler(
+                conn,
+                Arc::new(Config::default()),
+                Arc::default(),
+                menu_id,
+                menu,
+            )
+            .await;
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), status);
+                    let json_data = convert_response_to_json(resp).await;
+                    assert_eq!(json_data["error"].as_str(), Some(error));
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
+
+    // Test Case: Order creation fails with invalid data
+    #[tokio::test]
+    async fn test_create_order_handler_wrong_data() {
+        let conn = setup_test_db();
+        let order = OrderRequestBody {
+            table_id: 1,
+            menu_ids: vec![1, 2],
+            items: Vec::new(),
+        };
+        let result = create_order_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::default(),
+            Arc::default(),
+            None,
+            order,
+        )
+        .await;
+        // Expecting error due to missing menu entries
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data["error"].as_str(),
+                    Some("Unknown menu ID: 1; Unknown menu ID: 2")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Order creation fails with empty menu_ids
+    #[tokio::test]
+    async fn test_create_order_handler_wrong_data2() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let order = OrderRequestBody {
+            table_id: 1,
+            menu_ids: vec![],
+            items: Vec::new(),
+        };
+        let result = create_order_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::default(),
+            Arc::default(),
+            None,
+            order,
+        )
+        .await;
+        // Expecting error due to empty menu_ids
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Please Add Items"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Order creation succeeds with valid data
+    #[tokio::test]
+    async fn test_create_order_handler_correct_data() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute("UPDATE menus SET cooking_time = id * 4", [])
+            .expect("Failed to set cooking times");
+        let order = OrderRequestBody {
+            table_id: 1,
+            menu_ids: vec![1, 2],
+            items: Vec::new(),
+        };
+
+        let result = create_order_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::default(),
+            Arc::default(),
+            None,
+            order,
+        )
+        .await;
+        // Expecting successful order creation for table_id 1 with menu_ids 1 and 2
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+                assert_eq!(
+                    resp.headers().get("Location").and_then(|v| v.to_str().ok()),
+                    Some("/orders/1")
+                );
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["id"].as_i64(), Some(1));
+                assert_eq!(json_data["table_name"].as_str(), Some("T-01"));
+                // The new order comes back with the posted items, so no follow-up GET is needed
+                let items: Vec<_> = json_data["menus"]
+                    .as_array()
+                    .expect("Expected the order's menus")
+                    .iter()
+                    .map(|item| {
+                        (
+                            item["menu_id"].as_i64(),
+                            item["menu_name"].as_str(),
+                            item["quantity"].as_i64(),
+                            item["cooking_time"].as_i64(),
+                        )
+                    })
+                    .collect();
+                assert_eq!(
+                    items,
+                    vec![
+                        (Some(1), Some("M-01"), Some(1), Some(4)),
+                        (Some(2), Some("M-02"), Some(1), Some(8)),
+                    ]
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Order creation fails with a missing table
+    #[tokio::test]
+    async fn test_create_order_handler_missing_table() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let order = OrderRequestBody {
+            table_id: 99,
+            menu_ids: vec![1, 2],
+            items: Vec::new(),
+        };
+        let result = create_order_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::default(),
+            Arc::default(),
+            None,
+            order,
+        )
+        .await;
+        // Expecting error due to the missing table entry
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data["error"].as_str(),
+                    Some("Error creating order FOREIGN KEY constraint failed")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Order creation fails when a request adds more of an item than MAX_QUANTITY_PER_REQUEST
+    #[tokio::test]
+    async fn test_create_order_handler_quantity_cap() {
+        // Table 1 already has 5 of menu 1, more than the cap, which only limits what a request adds
+        let seeded_db = || {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+                .expect("Order creation failed");
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (1, 1, 50, 5)",
+                [],
+            )
+            .expect("OrderItems creation failed");
+            conn
+        };
+        let config = Arc::new(Config {
+            max_quantity_per_request: 2,
+            ..Config::default()
+        });
+
+        for (menu_ids, status) in [
+            (vec![1, 2, 1, 1], warp::http::StatusCode::BAD_REQUEST),
+            (vec![1, 2, 1], warp::http::StatusCode::OK),
+        ] {
+            let conn = seeded_db();
+            let order = OrderRequestBody {
+                table_id: 1,
+                menu_ids,
+                items: Vec::new(),
+            };
+            let result = create_order_handler(
+                conn,
+                config.clone(),
+                Arc::default(),
+                Arc::default(),
+                None,
+                order,
+            )
+            .await;
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), status);
+                    if status == warp::http::StatusCode::BAD_REQUEST {
+                        let json_data = convert_response_to_json(resp).await;
+                        assert_eq!(
+                            json_data["error"].as_str(),
+                            Some("Menu ID 1 can be added at most 2 times per request")
+                        );
+                    }
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
+
+    // Test Case: Ordering again for the same table adds to the existing order
+    #[tokio::test]
+    async fn test_create_order_handler_existing_order() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (1, 1, 12, 2)",
+            [],
+        )
+        .expect("OrderItems creation failed");
+        let order = OrderRequestBody {
+            table_id: 1,
+            menu_ids: vec![1, 2],
+            items: Vec::new(),
+        };
+
+        let result = create_order_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::default(),
+            Arc::default(),
+            None,
+            order,
+        )
+        .await;
+        // Expecting the items to be added to order 1 with a 200 carrying the whole order
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                assert!(resp.headers().get("Location").is_none());
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["id"].as_i64(), Some(1));
+                let menus = json_data["menus"]
+                    .as_array()
+                    .expect("Expected the order's menus");
+                let quantities: Vec<_> = menus
+                    .iter()
+                    .map(|item| (item["menu_id"].as_i64(), item["quantity"].as_i64()))
+                    .collect();
+                // Menu 1 already had 2 units, menu 2 is new
+                assert_eq!(quantities, vec![(Some(1), Some(3)), (Some(2), Some(1))]);
+                assert_eq!(menus[0]["cooking_time"].as_i64(), Some(18));
+                let total: i64 = menus
+                    .iter()
+                    .filter_map(|item| item["cooking_time"].as_i64())
+                    .sum();
+                assert_eq!(json_data["total_cooking_time"].as_i64(), Some(total));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Validating an order folds repeated menus and lists unknown ones
+    #[tokio::test]
+    async fn test_validate_order_handler() {
+        let validate = |menu_ids: Vec<i64>| {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            async move {
+                let order = OrderRequestBody {
+                    table_id: 1,
+                    menu_ids,
+                    items: Vec::new(),
+                };
+                match validate_order_handler(conn, Arc::new(Config::default()), order).await {
+                    Ok(rep) => {
+                        let resp = rep.into_response();
+                        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                        convert_response_to_json(resp).await
+                    }
+                    Err(_) => panic!("Unhandled Error"),
+                }
+            }
+        };
+
+        // Valid order
+        let json_data = validate(vec![1, 2]).await;
+        assert_eq!(json_data["valid"].as_bool(), Some(true));
+        assert_eq!(
+            json_data["items"],
+            json!([
+                {"menu_id": 1, "menu_name": "M-01", "quantity": 1},
+                {"menu_id": 2, "menu_name": "M-02", "quantity": 1}
+            ])
+        );
+        assert_eq!(json_data["errors"], json!([]));
+
+        // Repeated menu IDs become a quantity, in first-requested order
+        let json_data = validate(vec![3, 1, 3, 3]).await;
+        assert_eq!(json_data["valid"].as_bool(), Some(true));
+        assert_eq!(
+            json_data["items"],
+            json!([
+                {"menu_id": 3, "menu_name": "M-03", "quantity": 3},
+                {"menu_id": 1, "menu_name": "M-01", "quantity": 1}
+            ])
+        );
+
+        // Unknown menu IDs are reported once each while known ones are still listed
+        let json_data = validate(vec![1, 42, 42]).await;
+        assert_eq!(json_data["valid"].as_bool(), Some(false));
+        assert_eq!(json_data["items"].as_array().map(Vec::len), Some(1));
+        assert_eq!(json_data["errors"], json!(["Unknown menu ID: 42"]));
+    }
+
+    // Test Case: Remove an item from an order
+    #[tokio::test]
+    async fn test_remove_item_from_table_handler() {
+        let mut conn = setup_test_db();
+        setup_static_data(&conn);
+        // Start a transaction for creating order and order items
+        let tx = conn.transaction().expect("Transaction creation failed");
+
+        // Insert into the orders table
+        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+
+        // Get the last inserted order_id
+        let order_id = tx.last_insert_rowid();
+
+        // Insert into the order_items table using the obtained order_id
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+            [order_id, 1, 6],
+        )
+        .expect("OrderItems creation failed");
+
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+            [order_id, 2, 7],
+        )
+        .expect("OrderItems creation failed");
+
+        // Commit the transaction
+        tx.commit().expect("Commit failed");
+        let result = delete_order_item_handler(conn, Arc::default(), Arc::default(), 1, 2).await;
+        // Expecting to remove menu 2 from the order while keeping menu 1
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data["success"].as_str(),
+                    Some("Menu deleted successfully")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Removing all items from an order deletes the order
+    #[tokio::test]
+    async fn test_all_order_item_remove_handler() {
+        let mut conn = setup_test_db();
+        setup_static_data(&conn);
+        // Start a transaction for creating order and order items
+        let tx = conn.transaction().expect("Transaction creation failed");
+
+        // Insert into the orders table
+        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+
+        // Get the last inserted order_id
+        let order_id = tx.last_insert_rowid();
+
+        // Insert into the order_items table using the obtained order_id
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+            [order_id, 1, 6],
+        )
+        .expect("OrderItems creation failed");
+
+        // Commit the transaction
+        tx.commit().expect("Commit failed");
+        let result = delete_order_item_handler(conn, Arc::default(), Arc::default(), 1, 1).await;
+        // Expecting to remove menu 1 from the order and delete the order since no items remain
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data["success"].as_str(),
+                    Some("Menu deleted successfully and order deleted")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Removing an item with quantity greater than 1 reduces the quantity of the item
+    #[tokio::test]
+    async fn test_order_item_quantity_reduce_handler() {
+        let mut conn = setup_test_db();
+        setup_static_data(&conn);
+        // Start a transaction for creating order and order items
+        let tx = conn.transaction().expect("Transaction creation failed");
+
+        // Insert into the orders table
+        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+
+        // Get the last inserted order_id
+        let order_id = tx.last_insert_rowid();
+
+        // Insert into the order_items table using the obtained order_id
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4)",
+            [order_id, 1, 6, 2],
+        ).expect("OrderItems creation failed");
+
+        // Commit the transaction
+        tx.commit().expect("Commit failed");
+        let result = delete_order_item_handler(conn, Arc::default(), Arc::default(), 1, 1).await;
+        // Expecting to update the quantity of menu 1
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data["success"].as_str(),
+                    Some("Menu quantity updated successfully")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Retrieve a specific item from a table
+    #[tokio::test]
+    async fn test_get_item_from_table_handler() {
+        let mut conn = setup_test_db();
+        setup_static_data(&conn);
+        // Start a transaction for creating order and order items
+        let tx = conn.transaction().expect("Transaction creation failed");
+
+        // Insert into the orders table
+        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+
+        // Get the last inserted order_id
+        let order_id = tx.last_insert_rowid();
+
+        // Insert into the order_items table using the obtained order_id
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+            [order_id, 1, 6],
+        )
+        .expect("OrderItems creation failed");
+
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+            [order_id, 2, 7],
+        )
+        .expect("OrderItems creation failed");
+
+        // Commit the transaction
+        tx.commit().expect("Commit failed");
+
+        let result = get_order_item_for_table_handler(conn, 1, 2).await;
+        // Expecting to retrieve menu 2 from the table
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["menu_name"].as_str(), Some("M-02"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: List the items of an order by its ID
+    #[tokio::test]
+    async fn test_list_order_items_for_order_handler() {
+        let mut conn = setup_test_db();
+        setup_static_data(&conn);
+        // Start a transaction for creating order and order items
+        let tx = conn.transaction().expect("Transaction creation failed");
+
+        // Insert into the orders table
+        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+
+        // Get the last inserted order_id
+        let order_id = tx.last_insert_rowid();
+
+        // Insert into the order_items table using the obtained order_id
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+            [order_id, 1, 6],
+        )
+        .expect("OrderItems creation failed");
+
+        tx.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+            [order_id, 2, 7],
+        )
+        .expect("OrderItems creation failed");
+
+        // Commit the transaction
+        tx.commit().expect("Commit failed");
+
+        let result = list_order_items_for_order_handler(conn, order_id).await;
+        // Expecting both items of the order to be listed
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                let items = json_data.as_array().expect("Expected an array of items");
+                assert_eq!(items.len(), 2);
+                assert!(items
+                    .iter()
+                    .all(|item| item["order_id"].as_i64() == Some(order_id)));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: List the items of a table's order
+    #[tokio::test]
+    async fn test_list_order_items_for_table_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let order_id = OrderResponse::create(&conn, 2).expect("Order creation failed");
+        for menu_id in [3, 1] {
+            OrderItem::create(&conn, order_id, menu_id, 6, 1).expect("OrderItems creation failed");
+        }
+        let result = list_order_items_for_table_handler(conn, 2).await;
+        // Expecting both items, in the order they were added
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                let menu_ids: Vec<i64> = json_data
+                    .as_array()
+                    .expect("Expected an array of items")
+                    .iter()
+                    .filter_map(|item| item["menu_id"].as_i64())
+                    .collect();
+                assert_eq!(menu_ids, vec![3, 1]);
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: A table without an order lists no items
+    #[tokio::test]
+    async fn test_list_order_items_for_empty_table_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = list_order_items_for_table_handler(conn, 3).await;
+        // Expecting an empty array since table 3 exists but has no order
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data, json!([]));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Listing the items of a missing table returns NOT FOUND
+    #[tokio::test]
+    async fn test_list_order_items_for_missing_table_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = list_order_items_for_table_handler(conn, 42).await;
+        // Expecting an error since no table with ID 42 exists
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Table not found"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Listing the items of a missing order returns NOT FOUND
+    #[tokio::test]
+    async fn test_list_order_items_for_missing_order_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = list_order_items_for_order_handler(conn, 42).await;
+        // Expecting an error since no order with ID 42 exists
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Order not found"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
         }
     }
 
-    // Convert a warp Response to a serde JSON Value
-    async fn convert_response_to_json(resp: warp::http::Response<Body>) -> serde_json::Value {
-        let body_bytes = warp::hyper::body::to_bytes(resp.into_body()).await.unwrap();
-        let body_string = String::from_utf8_lossy(&body_bytes);
-        serde_json::from_str(&body_string).unwrap()
+    // Test Case: Ensuring menus reuses existing IDs and creates missing ones
+    #[tokio::test]
+    async fn test_ensure_menus_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let req_body = EnsureMenusRequestBody {
+            names: vec!["M-02".to_string(), "M-06".to_string(), "M-06".to_string()],
+        };
+        let result = ensure_menus_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::new(MenuCache::default()),
+            req_body,
+        )
+        .await;
+        // Expecting M-02 to keep its ID and M-06 to be created once
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["M-02"].as_i64(), Some(2));
+                assert_eq!(json_data["M-06"].as_i64(), Some(6));
+                assert_eq!(json_data.as_object().map(|map| map.len()), Some(2));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
     }
 
-    // Test Case: Menu Creation
+    // Test Case: Ensuring menus fails with an empty name list
     #[tokio::test]
-    async fn test_create_menu_handler() {
+    async fn test_ensure_menus_handler_wrong_data() {
+        let conn = setup_test_db();
+        let req_body = EnsureMenusRequestBody { names: vec![] };
+        let result = ensure_menus_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::new(MenuCache::default()),
+            req_body,
+        )
+        .await;
+        // Expecting error due to empty names
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Please Add Menu Names"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Orders whose table was deleted still appear in the order list
+    #[tokio::test]
+    async fn test_list_order_handler_orphaned_order() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        // Simulate an out-of-band deletion with foreign key enforcement off
+        conn.execute("PRAGMA foreign_keys = OFF;", [])
+            .expect("Failed to disable foreign key support");
+        for table_id in [1, 2] {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+        }
+        conn.execute("DELETE FROM tables WHERE id = 2", [])
+            .expect("Table deletion failed");
+
+        let result = list_order_handler(conn, OrderListQuery::default()).await;
+        // Expecting both orders, the orphaned one without a table name
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data.as_array().map(|orders| orders.len()), Some(2));
+                assert_eq!(json_data[0]["table_name"].as_str(), Some("T-01"));
+                assert_eq!(json_data[1]["table_id"].as_i64(), Some(2));
+                assert!(json_data[1]["table_name"].is_null());
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Orders list each item's price and a total of price times quantity
+    #[tokio::test]
+    async fn test_list_order_handler_total_price() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        for (name, price) in [("Ramen", 1250), ("Gyoza", 499)] {
+            let menu = Menu {
+                id: 0,
+                name: name.to_string(),
+                price,
+                category: None,
+                cooking_time: None,
+            };
+            Menu::create(&conn, &menu).expect("Menu creation failed");
+        }
+        conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+        let order_id = conn.last_insert_rowid();
+        // Two Ramen (menu 6) and three Gyoza (menu 7)
+        for (menu_id, quantity) in [(6, 2), (7, 3)] {
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4)",
+                [order_id, menu_id, 6, quantity],
+            )
+            .expect("OrderItems creation failed");
+        }
+
+        let result = list_order_handler(conn, OrderListQuery::default()).await;
+        // Expecting 2 * 12.50 + 3 * 4.99 = 39.97
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data[0]["total_price"].as_i64(), Some(3997));
+                assert_eq!(json_data[0]["menus"][0]["price"].as_i64(), Some(1250));
+                assert_eq!(json_data[0]["menus"][1]["price"].as_i64(), Some(499));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: A menu can't be created with a negative price
+    #[tokio::test]
+    async fn test_create_menu_handler_negative_price() {
         let conn = setup_test_db();
         let menu = Menu {
             id: 0,
             name: "Menu-01".to_string(),
+            price: -1,
+            category: None,
+            cooking_time: None,
         };
-        let result = create_menu_handler(conn, menu).await;
+        let result = create_menu_handler(
+            conn,
+            Arc::new(Config::default()),
+            Arc::new(MenuCache::default()),
+            menu,
+        )
+        .await;
         match result {
             Ok(rep) => {
                 let resp = rep.into_response();
-                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+                assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
                 let json_data = convert_response_to_json(resp).await;
-                assert_eq!(json_data["id"].as_i64(), Some(1));
+                assert_eq!(
+                    json_data["error"].as_str(),
+                    Some("Price must not be negative")
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Orders report when they were placed without the creator passing a timestamp
+    #[tokio::test]
+    async fn test_list_order_handler_created_at() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        OrderResponse::create(&conn, 1).expect("Order creation failed");
+
+        let result = list_order_handler(conn, OrderListQuery::default()).await;
+        // Expecting a "YYYY-MM-DD HH:MM:SS" timestamp
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                let created_at = json_data[0]["created_at"].as_str().unwrap_or_default();
+                assert_eq!(created_at.len(), 19, "{:?}", created_at);
+                assert!(created_at.starts_with("20"));
+                assert_eq!((&created_at[4..5], &created_at[10..11]), ("-", " "));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Upgrading a database without order timestamps stamps old and new orders
+    #[tokio::test]
+    async fn test_created_at_added_to_existing_orders() {
+        let conn = rusqlite::Connection::open_in_memory().expect("Failed to create test database");
+        conn.execute_batch(
+            "CREATE TABLE tables (id INTEGER PRIMARY KEY, code TEXT NOT NULL UNIQUE);
+            CREATE TABLE orders (id INTEGER PRIMARY KEY, table_id INTEGER NOT NULL, UNIQUE (table_id));
+            INSERT INTO tables (code) VALUES ('T-01'), ('T-02');
+            INSERT INTO orders (table_id) VALUES (1);",
+        )
+        .expect("Failed to create the old schema");
+        crate::db::initialize_schema(&conn);
+        OrderResponse::create(&conn, 2).expect("Order creation failed");
+
+        let missing: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM orders WHERE created_at IS NULL",
+                [],
+                |row| row.get(0),
+            )
+            .expect("Failed to count orders");
+        assert_eq!(missing, 0);
+    }
+
+    // Test Case: Upgrading a database without unit cooking times derives them from the totals
+    #[tokio::test]
+    async fn test_unit_cooking_time_added_to_existing_items() {
+        let conn = rusqlite::Connection::open_in_memory().expect("Failed to create test database");
+        conn.execute_batch(
+            "CREATE TABLE order_items (id INTEGER PRIMARY KEY, order_id INTEGER NOT NULL, menu_id INTEGER NOT NULL, cooking_time INTEGER NOT NULL, quantity INTEGER NOT NULL default 1);
+            INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (1, 1, 12, 3), (1, 2, 7, 2);",
+        )
+        .expect("Failed to create the old schema");
+        crate::db::initialize_schema(&conn);
+
+        let items: Vec<(i64, i64)> = conn
+            .prepare("SELECT unit_cooking_time, cooking_time FROM order_items ORDER BY id")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect()
+            })
+            .expect("Failed to read order items");
+        // A total that drifted off a multiple of the quantity is evened out
+        assert_eq!(items, vec![(4, 12), (3, 6)]);
+    }
+
+    // Test Case: A quantity change driving an item negative is refused with a clear internal error
+    #[tokio::test]
+    async fn test_order_items_non_negative() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let order_id = OrderResponse::create(&conn, 1).expect("Order creation failed");
+        let item_id =
+            OrderItem::create(&conn, order_id, 1, 5, 2).expect("OrderItems creation failed");
+
+        let err = OrderItem::add_quantity_of_existing_order_item(&conn, item_id, -3)
+            .expect_err("Expected the constraint to refuse the update");
+        assert_eq!(
+            ApiError::from(err),
+            ApiError::Integrity(
+                "Order item cooking time and quantity must not be negative".to_string()
+            )
+        );
+        let item: (i64, i64) = conn
+            .query_row(
+                "SELECT quantity, cooking_time FROM order_items WHERE id = ?1",
+                [item_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("Failed to read order item");
+        assert_eq!(item, (2, 10));
+
+        // Tables created before the constraint enforce it all the same
+        let conn = rusqlite::Connection::open_in_memory().expect("Failed to create test database");
+        conn.execute_batch(
+            "CREATE TABLE order_items (id INTEGER PRIMARY KEY, order_id INTEGER NOT NULL, menu_id INTEGER NOT NULL, cooking_time INTEGER NOT NULL, quantity INTEGER NOT NULL default 1);
+            INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (1, 1, 5);",
+        )
+        .expect("Failed to create the old schema");
+        crate::db::initialize_schema(&conn);
+        let err = OrderItem::add_quantity_of_existing_order_item(&conn, 1, -2)
+            .expect_err("Expected the trigger to refuse the update");
+        assert!(integrity_violation(&err).is_some(), "{}", err);
+        let err = conn
+            .execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (1, 2, -1)",
+                [],
+            )
+            .expect_err("Expected the trigger to refuse the insert");
+        assert!(integrity_violation(&err).is_some(), "{}", err);
+    }
+
+    // Test Case: A single order is returned with its table, items and totals
+    #[tokio::test]
+    async fn test_get_order_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        for table_id in [1, 2] {
+            let order_id = OrderResponse::create(&conn, table_id).expect("Order creation failed");
+            OrderItem::create(&conn, order_id, 3, 7, 1).expect("OrderItems creation failed");
+        }
+
+        let result = get_order_handler(conn, 2).await;
+        // Expecting the second order only
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["id"].as_i64(), Some(2));
+                assert_eq!(json_data["table_name"].as_str(), Some("T-02"));
+                assert_eq!(json_data["total_cooking_time"].as_i64(), Some(7));
+                assert_eq!(json_data["menus"][0]["menu_name"].as_str(), Some("M-03"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: A receipt lists each item's line total and adds tax rounded to the nearest cent
+    #[tokio::test]
+    async fn test_order_receipt_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute(
+            "UPDATE menus SET price = CASE id WHEN 1 THEN 1250 WHEN 2 THEN 499 ELSE 0 END",
+            [],
+        )
+        .expect("Failed to set prices");
+        let order_id = OrderResponse::create(&conn, 2).expect("Order creation failed");
+        OrderItem::create(&conn, order_id, 1, 5, 2).expect("OrderItems creation failed");
+        OrderItem::create(&conn, order_id, 2, 5, 1).expect("OrderItems creation failed");
+        let config = Arc::new(Config {
+            tax_rate: 0.08,
+            ..Config::default()
+        });
+
+        let result = order_receipt_handler(conn, config, 2).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                // 2 * 12.50 + 4.99 = 29.99, plus 8% tax of 2.3992 rounded to 2.40
+                assert_eq!(
+                    json_data,
+                    serde_json::json!({
+                        "table_code": "T-02",
+                        "items": [
+                            {"menu_name": "M-01", "quantity": 2, "unit_price": 1250, "line_total": 2500},
+                            {"menu_name": "M-02", "quantity": 1, "unit_price": 499, "line_total": 499},
+                        ],
+                        "subtotal": 2999,
+                        "tax": 240,
+                        "total": 3239,
+                    })
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: A table without an order or with a cancelled one has no receipt
+    #[tokio::test]
+    async fn test_order_receipt_handler_no_order() {
+        for status in [None, Some("cancelled")] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            if let Some(status) = status {
+                let order_id = OrderResponse::create(&conn, 1).expect("Order creation failed");
+                OrderItem::create(&conn, order_id, 1, 5, 2).expect("OrderItems creation failed");
+                OrderResponse::set_status(&conn, order_id, status).expect("Failed to set status");
+            }
+            let result = order_receipt_handler(conn, Arc::new(Config::default()), 1).await;
+            match result {
+                Ok(_) => {
+                    panic!("Expected the request to be rejected");
+                }
+                Err(rejection) => {
+                    assert_eq!(
+                        rejection.find::<ApiError>(),
+                        Some(&ApiError::NotFound("Order not found".to_string()))
+                    );
+                }
+            }
+        }
+    }
+
+    // Test Case: Fetching a missing order returns NOT FOUND
+    #[tokio::test]
+    async fn test_get_missing_order_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = get_order_handler(conn, 42).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data["error"].as_str(), Some("Order not found"));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: A single table is returned with its code
+    #[tokio::test]
+    async fn test_get_table_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = get_table_handler(conn, 3).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(json_data, serde_json::json!({"id": 3, "code": "T-03"}));
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Fetching a missing table returns NOT FOUND
+    #[tokio::test]
+    async fn test_get_missing_table_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = get_table_handler(conn, 42).await;
+        match result {
+            Ok(_) => {
+                panic!("Expected the request to be rejected");
+            }
+            Err(rejection) => {
+                assert_eq!(
+                    rejection.find::<ApiError>(),
+                    Some(&ApiError::NotFound("Table not found".to_string()))
+                );
+            }
+        }
+    }
+
+    // Test Case: A single menu is returned with its price and category
+    #[tokio::test]
+    async fn test_get_menu_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute(
+            "UPDATE menus SET price = 650, category = 'Mains' WHERE id = 2",
+            [],
+        )
+        .expect("Failed to update menu");
+
+        let result = get_menu_handler(conn, 2).await;
+        match result {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                assert_eq!(
+                    json_data,
+                    serde_json::json!({"id": 2, "name": "M-02", "price": 650, "category": "Mains"})
+                );
+            }
+            Err(_) => {
+                panic!("Unhandled Error");
+            }
+        }
+    }
+
+    // Test Case: Fetching a missing menu returns NOT FOUND
+    #[tokio::test]
+    async fn test_get_missing_menu_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        let result = get_menu_handler(conn, 42).await;
+        match result {
+            Ok(_) => {
+                panic!("Expected the request to be rejected");
+            }
+            Err(rejection) => {
+                assert_eq!(
+                    rejection.find::<ApiError>(),
+                    Some(&ApiError::NotFound("Menu not found".to_string()))
+                );
+            }
+        }
+    }
+
+    // Test Case: Menus are listed all together or filtered by category
+    #[tokio::test]
+    async fn test_list_menu_handler_category() {
+        let list = |category: Option<&str>| {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            for (name, category) in [
+                ("Lemonade", "Drinks"),
+                ("Curry", "Mains"),
+                ("Iced Tea", "Drinks"),
+            ] {
+                let menu = Menu {
+                    id: 0,
+                    name: name.to_string(),
+                    price: 0,
+                    category: Some(category.to_string()),
+                    cooking_time: None,
+                };
+                Menu::create(&conn, &menu).expect("Menu creation failed");
+            }
+            let query = MenuListQuery {
+                category: category.map(str::to_string),
+            };
+            list_menu_handler(conn, Arc::new(MenuCache::default()), query)
+        };
+
+        // Without a category every menu is listed, including those without one
+        match list(None).await {
+            Ok(rep) => {
+                let resp = rep.into_response();
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                let menus = json_data.as_array().expect("Expected a list of menus");
+                assert_eq!(menus.len(), 8);
+                let uncategorized = menus.iter().filter(|menu| menu["category"].is_null());
+                assert_eq!(uncategorized.count(), 5);
             }
             Err(_) => {
                 panic!("Unhandled Error");
             }
         }
+
+        for (category, expected) in [
+            ("Drinks", vec!["Iced Tea", "Lemonade"]),
+            ("Mains", vec!["Curry"]),
+            ("Desserts", vec![]),
+        ] {
+            match list(Some(category)).await {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                    let json_data = convert_response_to_json(resp).await;
+                    let menus = json_data.as_array().expect("Expected a list of menus");
+                    let names: Vec<_> = menus
+                        .iter()
+                        .filter_map(|menu| menu["name"].as_str())
+                        .collect();
+                    assert_eq!(names, expected);
+                    assert!(menus
+                        .iter()
+                        .all(|menu| menu["category"].as_str() == Some(category)));
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
     }
 
-    // Test Case: Table Creation
+    // Test Case: Menus are searched by a case-insensitive name substring, and an empty query is refused
     #[tokio::test]
-    async fn test_create_table_handler() {
-        let conn = setup_test_db();
-        let table = Table {
-            id: 0,
-            code: "Table-01".to_string(),
+    async fn test_search_menus_handler() {
+        let search = |q: &str| {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            for name in ["Lemonade", "Iced Lemon Tea"] {
+                let menu = Menu {
+                    id: 0,
+                    name: name.to_string(),
+                    price: 0,
+                    category: None,
+                    cooking_time: None,
+                };
+                Menu::create(&conn, &menu).expect("Menu creation failed");
+            }
+            let query = MenuSearchQuery { q: q.to_string() };
+            search_menus_handler(conn, query)
         };
-        let result = create_table_handler(conn, table).await;
-        match result {
+
+        match search("LEMON").await {
             Ok(rep) => {
                 let resp = rep.into_response();
-                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
                 let json_data = convert_response_to_json(resp).await;
-                assert_eq!(json_data["id"].as_i64(), Some(1));
+                let names: Vec<&str> = json_data
+                    .as_array()
+                    .expect("Expected a list of menus")
+                    .iter()
+                    .filter_map(|menu| menu["name"].as_str())
+                    .collect();
+                assert_eq!(names, vec!["Iced Lemon Tea", "Lemonade"]);
             }
             Err(_) => {
                 panic!("Unhandled Error");
             }
         }
+
+        // Neither an unknown name nor a LIKE wildcard matches anything
+        for q in ["Pizza", "%"] {
+            match search(q).await {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                    let json_data = convert_response_to_json(resp).await;
+                    assert_eq!(json_data, serde_json::json!([]));
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+
+        match search("  ").await {
+            Ok(_) => {
+                panic!("Expected the request to be rejected");
+            }
+            Err(rejection) => {
+                assert_eq!(
+                    rejection.find::<ApiError>(),
+                    Some(&ApiError::BadRequest(
+                        "Search query must not be empty".to_string()
+                    ))
+                );
+            }
+        }
     }
 
-    // Test Case: Order creation fails with invalid data
+    // Test Case: A row that fails to map makes the listings return INTERNAL_SERVER_ERROR instead of panicking
     #[tokio::test]
-    async fn test_create_order_handler_wrong_data() {
+    async fn test_list_handlers_row_mapping_failure() {
+        // A blob where a text code is expected can't be read as a String
         let conn = setup_test_db();
-        let order = OrderRequestBody {
-            table_id: 1,
-            menu_ids: vec![1, 2],
-        };
-        let result = create_order_handler(conn, order).await;
-        // Expecting error due to missing table and menu entries
-        match result {
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO tables (code) VALUES (X'FF00')", [])
+            .expect("Failed to insert table data");
+        match list_table_handler(conn).await {
             Ok(rep) => {
                 let resp = rep.into_response();
                 assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
-                let json_data = convert_response_to_json(resp).await;
-                assert_eq!(
-                    json_data["error"].as_str(),
-                    Some("Error creating order FOREIGN KEY constraint failed")
-                );
             }
             Err(_) => {
                 panic!("Unhandled Error");
             }
         }
-    }
 
-    // Test Case: Order creation fails with empty menu_ids
-    #[tokio::test]
-    async fn test_create_order_handler_wrong_data2() {
-        let mut conn = setup_test_db();
-        setup_static_data(&mut conn);
-        let order = OrderRequestBody {
-            table_id: 1,
-            menu_ids: vec![],
-        };
-        let result = create_order_handler(conn, order).await;
-        // Expecting error due to empty menu_ids
-        match result {
+        // Text where an integer price is expected can't be read as an i64
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute(
+            "INSERT INTO menus (name, price) VALUES ('M-06', 'free')",
+            [],
+        )
+        .expect("Failed to insert menu data");
+        match list_menu_handler(
+            conn,
+            Arc::new(MenuCache::default()),
+            MenuListQuery::default(),
+        )
+        .await
+        {
             Ok(rep) => {
                 let resp = rep.into_response();
-                assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
-                let json_data = convert_response_to_json(resp).await;
-                assert_eq!(json_data["error"].as_str(), Some("Please Add Items"));
+                assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
             }
             Err(_) => {
                 panic!("Unhandled Error");
@@ -581,24 +3869,41 @@ mod tests {
         }
     }
 
-    // Test Case: Order creation succeeds with valid data
+    // Test Case: Largest orders are sorted by total quantity and limited
     #[tokio::test]
-    async fn test_create_order_handler_correct_data() {
+    async fn test_list_largest_orders_handler() {
         let conn = setup_test_db();
         setup_static_data(&conn);
-        let order = OrderRequestBody {
-            table_id: 1,
-            menu_ids: vec![1, 2],
-        };
+        // Seed three orders with total quantities 2, 5 and 3
+        for (table_id, quantity) in [(1, 2), (2, 5), (3, 3)] {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+            let order_id = conn.last_insert_rowid();
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4)",
+                [order_id, 1, 6 * quantity, quantity],
+            )
+            .expect("OrderItems creation failed");
+        }
 
-        let result = create_order_handler(conn, order).await;
-        // Expecting successful order creation for table_id 1 with menu_ids 1 and 2
+        let query = LargestOrdersQuery {
+            limit: Some(2),
+            by: Some(OrderSizeMeasure::Quantity),
+        };
+        let result = list_largest_orders_handler(conn, query).await;
+        // Expecting the orders of tables 2 and 3, in that order
         match result {
             Ok(rep) => {
                 let resp = rep.into_response();
-                assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
                 let json_data = convert_response_to_json(resp).await;
-                assert_eq!(json_data["id"].as_i64(), Some(1));
+                let table_names: Vec<_> = json_data
+                    .as_array()
+                    .expect("Expected an array of orders")
+                    .iter()
+                    .map(|order| order["table_name"].as_str().unwrap_or_default().to_string())
+                    .collect();
+                assert_eq!(table_names, vec!["T-02", "T-03"]);
             }
             Err(_) => {
                 panic!("Unhandled Error");
@@ -606,47 +3911,42 @@ mod tests {
         }
     }
 
-    // Test Case: Remove an item from an order
+    // Test Case: Largest orders ranked by price weigh each item's quantity by its menu's price
     #[tokio::test]
-    async fn test_remove_item_from_table_handler() {
-        let mut conn = setup_test_db();
+    async fn test_list_largest_orders_handler_by_price() {
+        let conn = setup_test_db();
         setup_static_data(&conn);
-        // Start a transaction for creating order and order items
-        let tx = conn.transaction().expect("Transaction creation failed");
-
-        // Insert into the orders table
-        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
-            .expect("Order creation failed");
-
-        // Get the last inserted order_id
-        let order_id = tx.last_insert_rowid();
-
-        // Insert into the order_items table using the obtained order_id
-        tx.execute(
-            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
-            [order_id, 1, 6],
-        )
-        .expect("OrderItems creation failed");
-
-        tx.execute(
-            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
-            [order_id, 2, 7],
-        )
-        .expect("OrderItems creation failed");
+        conn.execute("UPDATE menus SET price = 100 * id", [])
+            .expect("Failed to update menus");
+        // Table 1 orders 4 x M-01 (400), table 2 2 x M-05 (1000) and table 3 3 x M-02 (600)
+        for (table_id, menu_id, quantity) in [(1, 1, 4), (2, 5, 2), (3, 2, 3)] {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+            let order_id = conn.last_insert_rowid();
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4)",
+                [order_id, menu_id, 5 * quantity, quantity],
+            )
+            .expect("OrderItems creation failed");
+        }
 
-        // Commit the transaction
-        tx.commit().expect("Commit failed");
-        let result = delete_order_item_handler(conn, 1, 2).await;
-        // Expecting to remove menu 2 from the order while keeping menu 1
+        let query = LargestOrdersQuery {
+            limit: None,
+            by: Some(OrderSizeMeasure::Price),
+        };
+        let result = list_largest_orders_handler(conn, query).await;
         match result {
             Ok(rep) => {
                 let resp = rep.into_response();
                 assert_eq!(resp.status(), warp::http::StatusCode::OK);
                 let json_data = convert_response_to_json(resp).await;
-                assert_eq!(
-                    json_data["success"].as_str(),
-                    Some("Menu deleted successfully")
-                );
+                let table_names: Vec<_> = json_data
+                    .as_array()
+                    .expect("Expected an array of orders")
+                    .iter()
+                    .map(|order| order["table_name"].as_str().unwrap_or_default().to_string())
+                    .collect();
+                assert_eq!(table_names, vec!["T-02", "T-03", "T-01"]);
             }
             Err(_) => {
                 panic!("Unhandled Error");
@@ -654,41 +3954,60 @@ mod tests {
         }
     }
 
-    // Test Case: Removing all items from an order deletes the order
+    // Test Case: Largest orders returns an empty array without orders and rejects a bad limit
     #[tokio::test]
-    async fn test_all_order_item_remove_handler() {
-        let mut conn = setup_test_db();
-        setup_static_data(&conn);
-        // Start a transaction for creating order and order items
-        let tx = conn.transaction().expect("Transaction creation failed");
-
-        // Insert into the orders table
-        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
-            .expect("Order creation failed");
+    async fn test_list_largest_orders_handler_edge_cases() {
+        let conn = setup_test_db();
+        let query = LargestOrdersQuery {
+            limit: None,
+            by: None,
+        };
+        let resp = list_largest_orders_handler(conn, query)
+            .await
+            .expect("Unhandled Error")
+            .into_response();
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(convert_response_to_json(resp).await, json!([]));
 
-        // Get the last inserted order_id
-        let order_id = tx.last_insert_rowid();
+        let conn = setup_test_db();
+        let query = LargestOrdersQuery {
+            limit: Some(0),
+            by: None,
+        };
+        let resp = list_largest_orders_handler(conn, query)
+            .await
+            .expect("Unhandled Error")
+            .into_response();
+        assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
 
-        // Insert into the order_items table using the obtained order_id
-        tx.execute(
-            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
-            [order_id, 1, 6],
-        )
-        .expect("OrderItems creation failed");
+    // Test Case: Menu statistics aggregate across orders and tables
+    #[tokio::test]
+    async fn test_get_menu_stats_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        // Menu 1 is ordered once on table 1 (quantity 1) and once on table 2 (quantity 3)
+        for (table_id, cooking_time, quantity) in [(1, 6, 1), (2, 30, 3)] {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+            let order_id = conn.last_insert_rowid();
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4)",
+                [order_id, 1, cooking_time, quantity],
+            )
+            .expect("OrderItems creation failed");
+        }
 
-        // Commit the transaction
-        tx.commit().expect("Commit failed");
-        let result = delete_order_item_handler(conn, 1, 1).await;
-        // Expecting to remove menu 1 from the order and delete the order since no items remain
+        let result = get_menu_stats_handler(conn, 1).await;
         match result {
             Ok(rep) => {
                 let resp = rep.into_response();
                 assert_eq!(resp.status(), warp::http::StatusCode::OK);
                 let json_data = convert_response_to_json(resp).await;
-                assert_eq!(
-                    json_data["success"].as_str(),
-                    Some("Menu deleted successfully and order deleted")
-                );
+                assert_eq!(json_data["times_ordered"].as_i64(), Some(2));
+                assert_eq!(json_data["total_quantity"].as_i64(), Some(4));
+                assert_eq!(json_data["average_cooking_time"].as_f64(), Some(9.0));
+                assert_eq!(json_data["distinct_tables"].as_i64(), Some(2));
             }
             Err(_) => {
                 panic!("Unhandled Error");
@@ -696,40 +4015,99 @@ mod tests {
         }
     }
 
-    // Test Case: Removing an item with quantity greater than 1 reduces the quantity of the item
+    // Test Case: Menu statistics are zeroed for an unordered menu and NOT FOUND for a missing one
     #[tokio::test]
-    async fn test_order_item_quantity_reduce_handler() {
-        let mut conn = setup_test_db();
+    async fn test_get_menu_stats_handler_edge_cases() {
+        let conn = setup_test_db();
         setup_static_data(&conn);
-        // Start a transaction for creating order and order items
-        let tx = conn.transaction().expect("Transaction creation failed");
-
-        // Insert into the orders table
-        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
-            .expect("Order creation failed");
+        let resp = get_menu_stats_handler(conn, 5)
+            .await
+            .expect("Unhandled Error")
+            .into_response();
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data = convert_response_to_json(resp).await;
+        assert_eq!(json_data["times_ordered"].as_i64(), Some(0));
+        assert_eq!(json_data["total_quantity"].as_i64(), Some(0));
+        assert_eq!(json_data["average_cooking_time"].as_f64(), Some(0.0));
+        assert_eq!(json_data["distinct_tables"].as_i64(), Some(0));
 
-        // Get the last inserted order_id
-        let order_id = tx.last_insert_rowid();
+        let conn = setup_test_db();
+        let resp = get_menu_stats_handler(conn, 42)
+            .await
+            .expect("Unhandled Error")
+            .into_response();
+        assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+    }
 
-        // Insert into the order_items table using the obtained order_id
-        tx.execute(
-            "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4)",
-            [order_id, 1, 6, 2],
-        ).expect("OrderItems creation failed");
+    // Test Case: Reordering fails unless every menu is listed exactly once
+    #[tokio::test]
+    async fn test_reorder_menus_handler_wrong_data() {
+        for menu_ids in [
+            vec![1, 2, 3, 4],
+            vec![1, 2, 3, 4, 5, 6],
+            vec![1, 1, 2, 3, 4],
+        ] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            let req_body = ReorderMenusRequestBody { menu_ids };
+            let result =
+                reorder_menus_handler(conn, Arc::new(MenuCache::default()), req_body).await;
+            // Expecting omissions, unknown IDs and repeats to be rejected
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+                    let json_data = convert_response_to_json(resp).await;
+                    assert_eq!(
+                        json_data["error"].as_str(),
+                        Some("Menu IDs must list every menu exactly once")
+                    );
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
 
-        // Commit the transaction
-        tx.commit().expect("Commit failed");
-        let result = delete_order_item_handler(conn, 1, 1).await;
-        // Expecting to update the quantity of menu 1
+    // Test Case: Items of several tables are returned in one map, including tables without items
+    #[tokio::test]
+    async fn test_list_order_items_for_tables_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        for (table_id, menu_ids) in [(1, vec![1, 2]), (3, vec![4])] {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+            let order_id = conn.last_insert_rowid();
+            for menu_id in menu_ids {
+                conn.execute(
+                    "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                    [order_id, menu_id, 6],
+                )
+                .expect("OrderItems creation failed");
+            }
+        }
+        let query = TableIdsQuery {
+            ids: "3, 1,2".to_string(),
+        };
+        let result = list_order_items_for_tables_handler(conn, query).await;
+        // Expecting tables 1 and 3 with their items and table 2 with an empty list
         match result {
             Ok(rep) => {
                 let resp = rep.into_response();
                 assert_eq!(resp.status(), warp::http::StatusCode::OK);
                 let json_data = convert_response_to_json(resp).await;
-                assert_eq!(
-                    json_data["success"].as_str(),
-                    Some("Menu quantity updated successfully")
-                );
+                let menu_ids = |table_id: &str| -> Vec<i64> {
+                    json_data[table_id]
+                        .as_array()
+                        .expect("Expected a list of items")
+                        .iter()
+                        .filter_map(|item| item["menu_id"].as_i64())
+                        .collect()
+                };
+                assert_eq!(menu_ids("1"), vec![1, 2]);
+                assert_eq!(menu_ids("2"), Vec::<i64>::new());
+                assert_eq!(menu_ids("3"), vec![4]);
             }
             Err(_) => {
                 panic!("Unhandled Error");
@@ -737,59 +4115,220 @@ mod tests {
         }
     }
 
-    // Test Case: Retrieve a specific item from a table
+    // Test Case: Malformed or too many table IDs are rejected
     #[tokio::test]
-    async fn test_get_item_from_table_handler() {
-        let mut conn = setup_test_db();
-        setup_static_data(&conn);
-        // Start a transaction for creating order and order items
-        let tx = conn.transaction().expect("Transaction creation failed");
+    async fn test_list_order_items_for_tables_handler_wrong_data() {
+        let too_many = (1..=MAX_TABLE_IDS as i64 + 1)
+            .map(|table_id| table_id.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        for ids in ["", "1,two", "1,,2", too_many.as_str()] {
+            let query = TableIdsQuery {
+                ids: ids.to_string(),
+            };
+            let result = list_order_items_for_tables_handler(setup_test_db(), query).await;
+            match result {
+                Ok(_) => {
+                    panic!("Expected {:?} to be rejected", ids);
+                }
+                Err(rejection) => {
+                    assert!(
+                        matches!(rejection.find::<ApiError>(), Some(ApiError::BadRequest(_))),
+                        "{}",
+                        ids
+                    );
+                }
+            }
+        }
+    }
 
-        // Insert into the orders table
-        tx.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
-            .expect("Order creation failed");
+    // Test Case: Copying an order fails for a missing source, a missing target or a busy target
+    #[tokio::test]
+    async fn test_copy_order_handler_wrong_data() {
+        for (order_id, table_id, status, error) in [
+            (42, 2, warp::http::StatusCode::NOT_FOUND, "Order not found"),
+            (1, 42, warp::http::StatusCode::NOT_FOUND, "Table not found"),
+            (
+                1,
+                2,
+                warp::http::StatusCode::CONFLICT,
+                "Table already has an open order",
+            ),
+        ] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            for table_id in [1, 2] {
+                conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                    .expect("Order creation failed");
+            }
+            let req_body = CopyOrderRequestBody { table_id };
+            let result =
+                copy_order_handler(conn, Arc::default(), Arc::default(), order_id, req_body).await;
+            match result {
+                Ok(rep) => {
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), status);
+                    let json_data = convert_response_to_json(resp).await;
+                    assert_eq!(json_data["error"].as_str(), Some(error));
+                }
+                Err(_) => {
+                    panic!("Unhandled Error");
+                }
+            }
+        }
+    }
 
-        // Get the last inserted order_id
-        let order_id = tx.last_insert_rowid();
+    // Test Case: An item is looked up by its order ID and menu ID
+    #[tokio::test]
+    async fn test_get_order_item_for_order_handler() {
+        for (order_id, menu_id, found) in [(2, 3, true), (1, 3, false), (42, 3, false)] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            for (table_id, menu_id) in [(1, 1), (2, 3)] {
+                conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                    .expect("Order creation failed");
+                let order_id = conn.last_insert_rowid();
+                conn.execute(
+                    "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                    [order_id, menu_id, 6],
+                )
+                .expect("OrderItems creation failed");
+            }
+            let result = get_order_item_for_order_handler(conn, order_id, menu_id).await;
+            // Expecting menu 3 only in order 2
+            match result {
+                Ok(rep) => {
+                    assert!(found);
+                    let resp = rep.into_response();
+                    assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                    let json_data = convert_response_to_json(resp).await;
+                    assert_eq!(json_data["order_id"].as_i64(), Some(2));
+                    assert_eq!(json_data["menu_name"].as_str(), Some("M-03"));
+                }
+                Err(rejection) => {
+                    assert!(!found);
+                    assert_eq!(
+                        rejection.find::<ApiError>(),
+                        Some(&ApiError::NotFound("No Item Found".to_string()))
+                    );
+                }
+            }
+        }
+    }
 
-        // Insert into the order_items table using the obtained order_id
-        tx.execute(
-            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
-            [order_id, 1, 6],
-        )
-        .expect("OrderItems creation failed");
+    // Test Case: The restaurant state lists tables by code, menus by name and orders by ID
+    #[tokio::test]
+    async fn test_current_state_is_sorted() {
+        let conn = setup_test_db();
+        for code in ["T-03", "T-01", "T-02"] {
+            conn.execute("INSERT INTO tables (code) VALUES (?1)", [code])
+                .expect("Failed to insert table data");
+        }
+        for name in ["Soup", "Pasta", "Cake"] {
+            conn.execute("INSERT INTO menus (name) VALUES (?1)", [name])
+                .expect("Failed to insert menu data");
+        }
+        for table_id in [3, 1, 2] {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+            let order_id = conn.last_insert_rowid();
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                [order_id, 1, 6],
+            )
+            .expect("OrderItems creation failed");
+        }
 
-        tx.execute(
+        let state = crate::models::get_current_state(&conn).expect("Failed to load state");
+        let codes: Vec<_> = state
+            .tables
+            .iter()
+            .map(|table| table.code.as_str())
+            .collect();
+        assert_eq!(codes, vec!["T-01", "T-02", "T-03"]);
+        let names: Vec<_> = state.menus.iter().map(|menu| menu.name.as_str()).collect();
+        assert_eq!(names, vec!["Cake", "Pasta", "Soup"]);
+        let order_ids: Vec<_> = state.orders.iter().map(|order| order.id).collect();
+        assert_eq!(order_ids, vec![1, 2, 3]);
+    }
+
+    // Test Case: The state handler returns every table, menu and order
+    #[tokio::test]
+    async fn test_get_state_handler() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+        let order_id = conn.last_insert_rowid();
+        conn.execute(
             "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
-            [order_id, 2, 7],
+            [order_id, 1, 6],
         )
         .expect("OrderItems creation failed");
 
-        // Commit the transaction
-        tx.commit().expect("Commit failed");
-
-        let result = get_order_item_for_table_handler(conn, 1, 2).await;
-        // Expecting to retrieve menu 2 from the table
+        let result = get_state_handler(conn).await;
+        // Expecting the three seeded tables, five menus and the single order
         match result {
             Ok(rep) => {
                 let resp = rep.into_response();
-                match resp.status() {
-                    // If item found, return the item
-                    warp::http::StatusCode::OK => {
-                        let json_data = convert_response_to_json(resp).await;
-                        assert_eq!(json_data["menu_name"].as_str(), Some("M-02"));
-                    }
-                    // If item not found, return an error
-                    warp::http::StatusCode::NOT_FOUND => {
-                        let json_data = convert_response_to_json(resp).await;
-                        assert_eq!(json_data["error"].as_str(), Some("No Item Found"));
-                    }
-                    _ => {}
-                }
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                let json_data = convert_response_to_json(resp).await;
+                let count = |key: &str| json_data[key].as_array().map(|items| items.len());
+                assert_eq!(count("tables"), Some(3));
+                assert_eq!(count("menus"), Some(5));
+                assert_eq!(count("orders"), Some(1));
+                assert_eq!(json_data["orders"][0]["id"].as_i64(), Some(order_id));
             }
             Err(_) => {
                 panic!("Unhandled Error");
             }
         }
     }
+
+    // Test Case: A value that can't be serialized gives a sanitized 500 instead of a malformed body
+    #[tokio::test]
+    async fn test_json_reply_serialization_failure() {
+        // JSON object keys must be strings, so a map keyed by tuples fails to serialize
+        let by_table_and_menu: std::collections::BTreeMap<(i64, i64), i64> =
+            [((1, 2), 3)].into_iter().collect();
+        let resp = json_reply(&by_table_and_menu, warp::http::StatusCode::OK);
+        assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+        let json_data = convert_response_to_json(resp).await;
+        assert_eq!(json_data, json!({"error": "Failed to serialize response"}));
+
+        // A non-finite float still yields valid JSON
+        let resp = json_reply(&[1.5, f64::NAN], warp::http::StatusCode::OK);
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(
+            resp.headers()[warp::http::header::CONTENT_TYPE],
+            "application/json"
+        );
+        let json_data = convert_response_to_json(resp).await;
+        assert_eq!(json_data, json!([1.5, null]));
+    }
+
+    // Test Case: Order items are listed in insertion order
+    #[tokio::test]
+    async fn test_list_order_items_in_insertion_order() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+        let order_id = conn.last_insert_rowid();
+        for menu_id in [4, 1, 5, 2] {
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                [order_id, menu_id, 6],
+            )
+            .expect("OrderItems creation failed");
+        }
+
+        let by_table = OrderItem::list_order_items(&conn, 1).expect("Failed to list items");
+        let by_order =
+            OrderItem::list_all_order_items(&conn, order_id).expect("Failed to list items");
+        for items in [by_table, by_order] {
+            let menu_ids: Vec<_> = items.iter().map(|item| item.menu_id).collect();
+            assert_eq!(menu_ids, vec![4, 1, 5, 2]);
+        }
+    }
 }
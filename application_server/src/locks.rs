@@ -0,0 +1,49 @@
+// src/locks.rs
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Per-table locks serializing mutating operations on the same table's order
+/// Operations on different tables hold different locks and proceed in parallel.
+#[derive(Debug, Default)]
+pub struct TableLocks {
+    locks: Mutex<HashMap<i64, Arc<AsyncMutex<()>>>>,
+}
+
+impl TableLocks {
+    /// Wait for exclusive access to a table, held until the returned guard is dropped
+    pub async fn lock(&self, table_id: i64) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|err| err.into_inner());
+            // Forget locks nobody holds or waits on so the map doesn't grow forever
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(table_id).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // Test Case: A second lock on the same table waits while different tables don't
+    #[tokio::test]
+    async fn test_table_locks() {
+        let locks = TableLocks::default();
+        let guard = locks.lock(1).await;
+
+        // Another table can be locked right away
+        let other = tokio::time::timeout(Duration::from_millis(50), locks.lock(2)).await;
+        assert!(other.is_ok());
+
+        // The same table has to wait until the first guard is released
+        let same = tokio::time::timeout(Duration::from_millis(50), locks.lock(1)).await;
+        assert!(same.is_err());
+        drop(guard);
+        let same = tokio::time::timeout(Duration::from_millis(50), locks.lock(1)).await;
+        assert!(same.is_ok());
+    }
+}
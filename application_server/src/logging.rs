@@ -0,0 +1,30 @@
+// src/logging.rs
+use tracing_subscriber::EnvFilter;
+
+/// Level used when `RUST_LOG` is unset or invalid
+const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Install the global tracing subscriber, printing events to stdout at the level set by `RUST_LOG`.
+/// Returns false when a subscriber is already installed, which leaves that one in place.
+pub fn init_tracing() -> bool {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_LEVEL));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .try_init()
+        .is_ok()
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test Case: The first call installs the subscriber and a repeated call neither panics nor replaces it
+    #[test]
+    fn test_init_tracing() {
+        assert!(init_tracing());
+        assert!(!init_tracing());
+        tracing::info!("tracing initialized");
+    }
+}
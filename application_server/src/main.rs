@@ -1,20 +1,64 @@
 // src/main.rs
+// The combined warp filter type nests deeper than the default limit allows
+#![recursion_limit = "256"]
+mod cache;
+mod config;
 mod db;
+mod error;
+mod events;
 mod handlers;
+mod locks;
+mod logging;
 mod models;
+mod naming;
 mod routes;
+mod shutdown;
+mod timeout;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Instant;
+use timeout::RequestTimeout;
+use warp::hyper::service::make_service_fn;
+use warp::hyper::Server;
 use warp::Filter;
 
 #[tokio::main]
 async fn main() {
+    // Remember when the process started so the info route can report uptime
+    let started_at = Instant::now();
+
+    // Emit logs at the level set by RUST_LOG
+    logging::init_tracing();
+
+    // Load the configuration once and share it with every route
+    let config = match config::Config::from_env() {
+        Ok(config) => Arc::new(config),
+        Err(err) => {
+            tracing::error!(error = %err, "invalid configuration");
+            std::process::exit(1);
+        }
+    };
+
     // Set up the database
-    db::initialize_db();
+    db::initialize_db(&config.db_path, config.db_busy_timeout());
 
     // Combine all defined routes
-    let routes = routes::restaurant_routes();
+    let routes = routes::restaurant_routes(config.clone(), started_at);
+
+    // Serve through hyper so the request timeout can cover the whole filter chain
+    let service = RequestTimeout::new(&config, warp::service(routes.with(warp::trace::request())));
+    let make_service = make_service_fn(move |_| {
+        let service = service.clone();
+        async move { Ok::<_, Infallible>(service) }
+    });
 
     println!("Starting the application server");
-    warp::serve(routes.with(warp::trace::request()))
-        .run(([127, 0, 0, 1], 3030))
-        .await;
+    // On SIGINT or SIGTERM, stop accepting connections and let in-flight requests finish
+    let server = Server::bind(&config.addr())
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown::shutdown_signal());
+    if let Err(err) = server.await {
+        tracing::error!(error = %err, "server error");
+        std::process::exit(1);
+    }
 }
@@ -1,10 +1,14 @@
 // src/models.rs
-use rusqlite::params;
 use rusqlite::Connection;
-use serde;
+use rusqlite::{params, params_from_iter, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Longest menu name accepted, so that names fit on a receipt line
+pub const MAX_MENU_NAME_LENGTH: usize = 64;
 
 /// Represents the state of the Restaurant
+#[allow(dead_code)]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RestaurantState {
     pub tables: Vec<TableResponse>,
@@ -16,10 +20,27 @@ pub struct RestaurantState {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Table {
     #[serde(skip)]
+    #[allow(dead_code)]
     pub id: i64,
     pub code: String,
 }
 
+/// Represents the outcome of one code of a bulk table creation request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableCreation {
+    pub code: String,
+    pub id: i64,
+    pub created: bool, // False when a table with the code already existed
+}
+
+/// Represents the outcome of a table deletion request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableDeletion {
+    Deleted,
+    NotFound,
+    HasActiveOrder,
+}
+
 /// Represents a response containing table details
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TableResponse {
@@ -27,26 +48,196 @@ pub struct TableResponse {
     pub code: String,
 }
 
+/// Represents a table with the load of its open order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableLoadResponse {
+    pub id: i64,
+    pub code: String,
+    pub total_cooking_time: i64, // 0 when the table has no open order
+}
+
+/// Represents the directions a listing can be sorted in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Represents the query parameters for listing tables by load
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableLoadQuery {
+    pub direction: Option<SortDirection>,
+}
+
 /// Represents a menu creation request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Menu {
     #[serde(skip)]
+    #[allow(dead_code)]
     pub id: i64,
     pub name: String,
+    #[serde(default)]
+    pub price: i64, // In cents; only used when creating a menu
+    #[serde(default)]
+    pub category: Option<String>, // e.g. "Drinks"; only used when creating a menu
+    #[serde(default)]
+    pub cooking_time: Option<i64>, // Minutes per unit for new order items; only used when creating a menu
+}
+
+/// Represents the outcome of a menu deletion request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuDeletion {
+    Deleted,
+    NotFound,
+    InUse,
 }
 
 /// Represents a response containing menu details
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MenuResponse {
     pub id: i64,
     pub name: String,
+    pub price: i64,               // In cents
+    pub category: Option<String>, // None for menus without a category
+}
+
+/// Represents the optional filter of the menu listing
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MenuListQuery {
+    pub category: Option<String>,
+}
+
+/// Represents the `q` substring of a menu search
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MenuSearchQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+/// Represents a response containing order statistics for a menu
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuStatsResponse {
+    pub menu_id: i64,
+    pub times_ordered: i64,
+    pub total_quantity: i64,
+    pub average_cooking_time: f64, // Average cooking time of a single unit
+    pub distinct_tables: i64,
+}
+
+/// Represents how many units of a menu all current orders hold together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MenuPopularity {
+    pub menu_id: i64,
+    pub menu_name: String,
+    pub total_quantity: i64,
+}
+
+/// Represents a request to ensure a set of menus exist
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EnsureMenusRequestBody {
+    pub names: Vec<String>,
+}
+
+/// Represents a request to set the display order of all menus
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReorderMenusRequestBody {
+    pub menu_ids: Vec<i64>,
+}
+
+/// Represents a request to set a menu's cooking time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MenuCookingTimeRequestBody {
+    pub cooking_time: i64,
+}
+
+/// Represents one requested line of an order, ordering `quantity` units of a menu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderItemRequest {
+    pub menu_id: i64,
+    pub quantity: i64,
 }
 
-/// Represents an order creation request
+/// Represents an order creation request.
+/// Menus can be listed once per unit in `menu_ids`, with a quantity in `items`, or both.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrderRequestBody {
     pub table_id: i64,
+    #[serde(default)]
     pub menu_ids: Vec<i64>,
+    #[serde(default)]
+    pub items: Vec<OrderItemRequest>,
+}
+
+/// Represents one line of a validated order, with repeated menu IDs folded into a quantity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatedOrderItem {
+    pub menu_id: i64,
+    pub menu_name: String,
+    pub quantity: i64,
+}
+
+/// Represents the outcome of validating an order request, without persisting anything
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderValidationResponse {
+    pub valid: bool,
+    pub items: Vec<ValidatedOrderItem>, // Known menus in the order they were first requested
+    pub errors: Vec<String>,
+}
+
+/// Statuses an order can be in, starting at `pending`
+pub const ORDER_STATUSES: [&str; 4] = ["pending", "cooking", "served", "cancelled"];
+
+/// Condition on `orders` that keeps a table's active order. A cancelled order is closed: it no
+/// longer holds its table and is released when the table is ordered for again. A served order
+/// stays active until it's deleted, since the table still has to pay for it.
+pub const ACTIVE_ORDER_FILTER: &str = "orders.status != 'cancelled'";
+
+/// Represents a request to change an order's status
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrderStatusRequestBody {
+    pub status: String,
+}
+
+/// Represents a request to set an order item's quantity, 0 removing the item
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemQuantityRequestBody {
+    pub quantity: i64,
+}
+
+/// Represents a request to copy an order's items to another table
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CopyOrderRequestBody {
+    pub table_id: i64,
+}
+
+/// Represents the measures an order's size can be ranked by
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderSizeMeasure {
+    Quantity,
+    CookingTime,
+    Price,
+}
+
+/// Represents the query parameters for listing the largest orders
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LargestOrdersQuery {
+    pub limit: Option<i64>,
+    pub by: Option<OrderSizeMeasure>,
+}
+
+/// Represents the pagination query parameters of the order listing, e.g. `?limit=50&offset=100`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct OrderListQuery {
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+/// Represents the query string of the items-by-tables listing, e.g. `?ids=1,2,3`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableIdsQuery {
+    pub ids: String,
 }
 
 /// Represents a response containing order details
@@ -54,19 +245,77 @@ pub struct OrderRequestBody {
 pub struct OrderResponse {
     pub id: i64,
     pub table_id: i64,
-    pub table_name: String,
-    pub total_cooking_time: i32, // Total cooking time calculated from order items
+    pub table_name: Option<String>, // None when the order's table no longer exists
+    pub status: String,             // One of ORDER_STATUSES
+    pub created_at: String,         // UTC time the order was placed, e.g. "2024-05-01 12:30:00"
+    pub total_cooking_time: i32,    // Total cooking time calculated from order items
+    pub total_price: i64,           // Total price in cents calculated from order items
     pub menus: Vec<OrderItemResponse>,
 }
 
+/// Represents an order's totals as kept in the `order_summary` table by triggers on `order_items`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderSummary {
+    pub order_id: i64,
+    pub table_code: Option<String>, // None when the order's table no longer exists
+    pub total_cooking_time: i64,
+    pub item_count: i64,
+    pub total_quantity: i64,
+}
+
+/// Represents the cooking time a table's open order still needs from the kitchen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KitchenLoad {
+    pub table_id: i64,
+    pub table_code: String,
+    pub total_cooking_time: i64,
+}
+
+/// Represents a table that currently has an order, with the order's total cooking time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OccupiedTable {
+    pub table_id: i64,
+    pub code: String,
+    pub total_cooking_time: i64,
+}
+
+/// Represents one line of an order receipt, with amounts in cents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReceiptLine {
+    pub menu_name: String,
+    pub quantity: i64,
+    pub unit_price: i64,
+    pub line_total: i64, // unit_price * quantity
+}
+
+/// Represents a printable receipt for a table's order, with amounts in cents at current menu prices
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Receipt {
+    pub table_code: String,
+    pub items: Vec<ReceiptLine>,
+    pub subtotal: i64,
+    pub tax: i64, // subtotal * TAX_RATE, rounded to the nearest cent
+    pub total: i64,
+}
+
+/// Represents the response a keyed order creation was answered with, replayed when the key is reused
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotentResponse {
+    pub request: Option<String>, // The JSON request the key was used with, None for keys stored before it was kept
+    pub status: u16,
+    pub location: Option<String>, // None unless the request created the order
+    pub body: String,             // The JSON body exactly as it was sent
+}
+
 /// Represents an order item creation request
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OrderItem {
     #[serde(skip)]
+    #[allow(dead_code)]
     pub id: i64,
     pub order_id: i64,
     pub menu_id: i64,
-    pub cooking_tme: i64,
+    pub cooking_time: i64,
 }
 
 /// Represents a response containing order item details
@@ -78,9 +327,18 @@ pub struct OrderItemResponse {
     pub menu_name: String,
     pub cooking_time: i64,
     pub quantity: i64,
+    pub price: i64,   // Unit price of the menu, in cents
+    pub served: bool, // Whether the item has been delivered to the table
 }
 
+/// Columns of an order item response, aliased so rows are mapped by name in `OrderItemResponse::from_row`
+const ORDER_ITEM_COLUMNS: &str = "order_items.id AS id, order_items.order_id AS order_id, order_items.menu_id AS menu_id, m.name AS menu_name, order_items.quantity AS quantity, order_items.unit_cooking_time * order_items.quantity AS cooking_time, m.price AS price, order_items.served AS served";
+
+/// Columns of an order response read from `order_summary`, which keeps the table name and cooking time
+const ORDER_SUMMARY_SELECT: &str = "SELECT s.order_id, orders.table_id, s.table_code, s.total_cooking_time, orders.status, orders.created_at FROM order_summary as s JOIN orders on orders.id=s.order_id";
+
 /// Function to get the current state of the Restaurant
+#[allow(dead_code)]
 pub fn get_current_state(conn: &Connection) -> Result<RestaurantState, rusqlite::Error> {
     let tables = Table::list(conn)?;
     let menus = Menu::list(conn)?;
@@ -103,9 +361,9 @@ impl Table {
         Ok(last_inserted_id)
     }
 
-    /// List all tables
+    /// List all tables, sorted by code
     pub fn list(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<TableResponse>> {
-        let mut stmt = conn.prepare("SELECT * FROM tables")?;
+        let mut stmt = conn.prepare("SELECT * FROM tables ORDER BY code")?;
         let rows = stmt.query_map(params![], |row| {
             Ok(TableResponse {
                 id: row.get(0)?,
@@ -113,7 +371,117 @@ impl Table {
             })
         })?;
         // Collect and return the results as a vector
-        Ok(rows.map(|result| result.unwrap()).collect())
+        rows.collect()
+    }
+
+    /// List all tables by the total cooking time of their open order, read from `order_summary`.
+    /// Tables without an active order have a load of 0; ties are sorted by code.
+    pub fn list_by_load(
+        conn: &rusqlite::Connection,
+        direction: SortDirection,
+    ) -> rusqlite::Result<Vec<TableLoadResponse>> {
+        let order = match direction {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        };
+        let query = format!(
+            "SELECT t.id, t.code, COALESCE(SUM(s.total_cooking_time), 0) AS load
+            FROM tables AS t
+            LEFT JOIN orders ON orders.table_id = t.id AND {}
+            LEFT JOIN order_summary AS s ON s.order_id = orders.id
+            GROUP BY t.id
+            ORDER BY load {}, t.code",
+            ACTIVE_ORDER_FILTER, order
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let rows = stmt.query_map(params![], |row| {
+            Ok(TableLoadResponse {
+                id: row.get(0)?,
+                code: row.get(1)?,
+                total_cooking_time: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// List the tables that have an active order, sorted by code, with the total cooking time read from `order_summary`
+    pub fn list_occupied(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<OccupiedTable>> {
+        let query = format!(
+            "SELECT t.id, t.code, COALESCE(SUM(s.total_cooking_time), 0)
+            FROM tables AS t
+            INNER JOIN orders ON orders.table_id = t.id AND {}
+            LEFT JOIN order_summary AS s ON s.order_id = orders.id
+            GROUP BY t.id
+            ORDER BY t.code",
+            ACTIVE_ORDER_FILTER
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let rows = stmt.query_map(params![], |row| {
+            Ok(OccupiedTable {
+                table_id: row.get(0)?,
+                code: row.get(1)?,
+                total_cooking_time: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Get a single table by its ID
+    pub fn get_by_id(
+        conn: &rusqlite::Connection,
+        table_id: i64,
+    ) -> rusqlite::Result<Option<TableResponse>> {
+        conn.query_row(
+            "SELECT id, code FROM tables WHERE id = ?1",
+            params![table_id],
+            |row| {
+                Ok(TableResponse {
+                    id: row.get(0)?,
+                    code: row.get(1)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    /// Check if a table exists by its ID
+    pub fn exists(conn: &rusqlite::Connection, table_id: i64) -> rusqlite::Result<bool> {
+        let query = "SELECT COUNT(*) FROM tables WHERE id = ?";
+        let count: i64 = conn.query_row(query, params![table_id], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    /// Change a table's code, returning whether it exists
+    pub fn update(
+        conn: &rusqlite::Connection,
+        table_id: i64,
+        table: &Table,
+    ) -> rusqlite::Result<bool> {
+        let updated = conn.execute(
+            "UPDATE tables SET code = ?1 WHERE id = ?2",
+            params![table.code, table_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Delete a table unless an active order still references it, along with any closed order.
+    /// Run it in a transaction so a closed order isn't removed without the table.
+    pub fn delete(conn: &rusqlite::Connection, table_id: i64) -> rusqlite::Result<TableDeletion> {
+        if OrderResponse::get_existing_order_id(conn, table_id)?.is_some() {
+            return Ok(TableDeletion::HasActiveOrder);
+        }
+        OrderResponse::release_closed_order(conn, table_id)?;
+        let deleted = conn.execute("DELETE FROM tables WHERE id = ?1", params![table_id])?;
+        if deleted > 0 {
+            Ok(TableDeletion::Deleted)
+        } else {
+            Ok(TableDeletion::NotFound)
+        }
+    }
+
+    /// Count all tables
+    pub fn count(conn: &rusqlite::Connection) -> rusqlite::Result<i64> {
+        conn.query_row("SELECT COUNT(*) FROM tables", params![], |row| row.get(0))
     }
 
     /// Get the ID of an existing table by its code
@@ -132,27 +500,339 @@ impl Table {
     }
 }
 
+/// Functions for validating order requests
+impl OrderRequestBody {
+    /// Check the requested menus exist and fold repeated menu IDs and `items` into quantities,
+    /// none of which may exceed `max_quantity` since that's how much the request adds to the item.
+    /// Both order creation and `POST /orders/validate` go through this, so they accept the same orders.
+    pub fn validate(
+        &self,
+        conn: &rusqlite::Connection,
+        max_quantity: u64,
+    ) -> rusqlite::Result<OrderValidationResponse> {
+        let mut items: Vec<ValidatedOrderItem> = Vec::new();
+        let mut unknown_menu_ids = Vec::new();
+        let mut errors = Vec::new();
+        if self.menu_ids.is_empty() && self.items.is_empty() {
+            errors.push("Please Add Items".to_string());
+        }
+        let requested = self
+            .menu_ids
+            .iter()
+            .map(|&menu_id| (menu_id, 1))
+            .chain(self.items.iter().map(|item| (item.menu_id, item.quantity)));
+        for (menu_id, quantity) in requested {
+            if quantity < 1 {
+                errors.push(format!(
+                    "Quantity for menu ID {} must be at least 1",
+                    menu_id
+                ));
+            } else if let Some(item) = items.iter_mut().find(|item| item.menu_id == menu_id) {
+                item.quantity = item.quantity.saturating_add(quantity);
+            } else if !unknown_menu_ids.contains(&menu_id) {
+                match Menu::name(conn, menu_id)? {
+                    Some(menu_name) => items.push(ValidatedOrderItem {
+                        menu_id,
+                        menu_name,
+                        quantity,
+                    }),
+                    None => {
+                        unknown_menu_ids.push(menu_id);
+                        errors.push(format!("Unknown menu ID: {}", menu_id));
+                    }
+                }
+            }
+        }
+        for item in &items {
+            if item.quantity as u64 > max_quantity {
+                errors.push(format!(
+                    "Menu ID {} can be added at most {} times per request",
+                    item.menu_id, max_quantity
+                ));
+            }
+        }
+        Ok(OrderValidationResponse {
+            valid: errors.is_empty(),
+            items,
+            errors,
+        })
+    }
+}
+
 /// Functions for managing Menu records
 impl Menu {
+    /// Check a menu name and return it trimmed, or a message describing why it was rejected.
+    /// Names must be non-empty and at most `MAX_MENU_NAME_LENGTH` characters; when
+    /// `allowed_chars` is set they may only contain letters, digits, spaces and those characters.
+    pub fn validate_name(name: &str, allowed_chars: Option<&str>) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Please Add Menu Name".to_string());
+        }
+        if name.chars().count() > MAX_MENU_NAME_LENGTH {
+            return Err(format!(
+                "Menu name must be at most {} characters",
+                MAX_MENU_NAME_LENGTH
+            ));
+        }
+        if let Some(allowed_chars) = allowed_chars {
+            let mut disallowed = String::new();
+            for c in name.chars() {
+                let allowed = c.is_alphanumeric() || c == ' ' || allowed_chars.contains(c);
+                if !allowed && !disallowed.contains(c) {
+                    disallowed.push(c);
+                }
+            }
+            if !disallowed.is_empty() {
+                return Err(format!(
+                    "Menu name contains disallowed characters: {:?}",
+                    disallowed
+                ));
+            }
+        }
+        Ok(name.to_string())
+    }
+
     /// Create a new menu item
     pub fn create(conn: &rusqlite::Connection, menu: &Menu) -> rusqlite::Result<i64> {
-        conn.execute("INSERT INTO menus (name) VALUES (?1)", params![menu.name])?;
+        conn.execute(
+            "INSERT INTO menus (name, price, category, cooking_time) VALUES (?1, ?2, ?3, ?4)",
+            params![menu.name, menu.price, menu.category, menu.cooking_time],
+        )?;
         // Retrieve the ID of the last inserted row
         let last_inserted_id = conn.last_insert_rowid();
         Ok(last_inserted_id)
     }
 
-    /// List all menu items
+    /// Rename a menu item, returning whether it exists
+    pub fn update(
+        conn: &rusqlite::Connection,
+        menu_id: i64,
+        menu: &Menu,
+    ) -> rusqlite::Result<bool> {
+        let updated = conn.execute(
+            "UPDATE menus SET name = ?1 WHERE id = ?2",
+            params![menu.name, menu_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Delete a menu unless an order item still references it
+    pub fn delete(conn: &rusqlite::Connection, menu_id: i64) -> rusqlite::Result<MenuDeletion> {
+        let in_use = conn
+            .prepare("SELECT 1 FROM order_items WHERE menu_id = ?1")?
+            .exists(params![menu_id])?;
+        if in_use {
+            return Ok(MenuDeletion::InUse);
+        }
+        let deleted = conn.execute("DELETE FROM menus WHERE id = ?1", params![menu_id])?;
+        if deleted > 0 {
+            Ok(MenuDeletion::Deleted)
+        } else {
+            Ok(MenuDeletion::NotFound)
+        }
+    }
+
+    /// Get the IDs for the given menu names, creating any that don't exist yet
+    pub fn ensure(
+        conn: &rusqlite::Connection,
+        names: &[String],
+    ) -> rusqlite::Result<BTreeMap<String, i64>> {
+        let mut menu_ids = BTreeMap::new();
+        for name in names {
+            let menu = Menu {
+                id: 0,
+                name: name.clone(),
+                price: 0,
+                category: None,
+                cooking_time: None,
+            };
+            // Reuse the existing menu if one with the same name exists, otherwise create it
+            let menu_id = match Menu::get_existing_menu_id(conn, &menu)? {
+                Some(menu_id) => menu_id,
+                None => Menu::create(conn, &menu)?,
+            };
+            menu_ids.insert(menu.name, menu_id);
+        }
+        Ok(menu_ids)
+    }
+
+    /// List all menu items, sorted by display position, then by name for menus without one
     pub fn list(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<MenuResponse>> {
-        let mut stmt = conn.prepare("SELECT * FROM menus")?;
-        let rows = stmt.query_map(params![], |row| {
+        Menu::list_by_category(conn, None)
+    }
+
+    /// List the menu items in `category`, or all of them when it is None, sorted like `list`
+    pub fn list_by_category(
+        conn: &rusqlite::Connection,
+        category: Option<&str>,
+    ) -> rusqlite::Result<Vec<MenuResponse>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, price, category FROM menus
+            WHERE ?1 IS NULL OR category = ?1
+            ORDER BY position IS NULL, position, name",
+        )?;
+        let rows = stmt.query_map(params![category], |row| {
             Ok(MenuResponse {
                 id: row.get(0)?,
                 name: row.get(1)?,
+                price: row.get(2)?,
+                category: row.get(3)?,
             })
         })?;
         // Collect and return the results as a vector
-        Ok(rows.map(|result| result.unwrap()).collect())
+        rows.collect()
+    }
+
+    /// List the menu items whose name contains `query`, ignoring case, sorted like `list`.
+    /// `%` and `_` in the query match themselves rather than acting as wildcards.
+    pub fn search(conn: &rusqlite::Connection, query: &str) -> rusqlite::Result<Vec<MenuResponse>> {
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let mut stmt = conn.prepare(
+            "SELECT id, name, price, category FROM menus
+            WHERE name LIKE '%' || ?1 || '%' ESCAPE '\\'
+            ORDER BY position IS NULL, position, name",
+        )?;
+        let rows = stmt.query_map(params![escaped], |row| {
+            Ok(MenuResponse {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                price: row.get(2)?,
+                category: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Get the IDs of all menu items, sorted by ID
+    pub fn ids(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<i64>> {
+        let mut stmt = conn.prepare("SELECT id FROM menus ORDER BY id")?;
+        let rows = stmt.query_map(params![], |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// Set the display position of each menu item to its index in `menu_ids`
+    pub fn reorder(conn: &rusqlite::Connection, menu_ids: &[i64]) -> rusqlite::Result<()> {
+        let mut stmt = conn.prepare("UPDATE menus SET position = ?1 WHERE id = ?2")?;
+        for (position, menu_id) in menu_ids.iter().enumerate() {
+            stmt.execute(params![position as i64, menu_id])?;
+        }
+        Ok(())
+    }
+
+    /// Set the cooking time new order items of a menu get, returning whether the menu exists.
+    /// Order items already placed keep the cooking time they were created with.
+    pub fn set_cooking_time(
+        conn: &rusqlite::Connection,
+        menu_id: i64,
+        cooking_time: i64,
+    ) -> rusqlite::Result<bool> {
+        let updated = conn.execute(
+            "UPDATE menus SET cooking_time = ?1 WHERE id = ?2",
+            params![cooking_time, menu_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Get a menu's calibrated cooking time, None when it has none or doesn't exist
+    pub fn cooking_time(
+        conn: &rusqlite::Connection,
+        menu_id: i64,
+    ) -> rusqlite::Result<Option<i64>> {
+        let cooking_time = conn
+            .query_row(
+                "SELECT cooking_time FROM menus WHERE id = ?1",
+                params![menu_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(cooking_time.flatten())
+    }
+
+    /// Get a menu's name by its ID, None when it doesn't exist
+    pub fn name(conn: &rusqlite::Connection, menu_id: i64) -> rusqlite::Result<Option<String>> {
+        conn.query_row(
+            "SELECT name FROM menus WHERE id = ?1",
+            params![menu_id],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    /// Count all menu items
+    pub fn count(conn: &rusqlite::Connection) -> rusqlite::Result<i64> {
+        conn.query_row("SELECT COUNT(*) FROM menus", params![], |row| row.get(0))
+    }
+
+    /// Get a single menu item by its ID
+    pub fn get_by_id(
+        conn: &rusqlite::Connection,
+        menu_id: i64,
+    ) -> rusqlite::Result<Option<MenuResponse>> {
+        conn.query_row(
+            "SELECT id, name, price, category FROM menus WHERE id = ?1",
+            params![menu_id],
+            |row| {
+                Ok(MenuResponse {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    price: row.get(2)?,
+                    category: row.get(3)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    /// Check if a menu item exists by its ID
+    pub fn exists(conn: &rusqlite::Connection, menu_id: i64) -> rusqlite::Result<bool> {
+        let query = "SELECT COUNT(*) FROM menus WHERE id = ?";
+        let count: i64 = conn.query_row(query, params![menu_id], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    /// Aggregate how a menu item appears across order items
+    pub fn stats(conn: &rusqlite::Connection, menu_id: i64) -> rusqlite::Result<MenuStatsResponse> {
+        let query = "
+        SELECT COUNT(*),
+            COALESCE(SUM(oi.quantity), 0),
+            COALESCE(CAST(SUM(oi.cooking_time) AS REAL) / SUM(oi.quantity), 0.0),
+            COUNT(DISTINCT orders.table_id)
+        FROM order_items oi
+        JOIN orders ON orders.id = oi.order_id
+        WHERE oi.menu_id = ?1";
+        conn.query_row(query, params![menu_id], |row| {
+            Ok(MenuStatsResponse {
+                menu_id,
+                times_ordered: row.get(0)?,
+                total_quantity: row.get(1)?,
+                average_cooking_time: row.get(2)?,
+                distinct_tables: row.get(3)?,
+            })
+        })
+    }
+
+    /// Sum the ordered quantity of every menu across current order items, most ordered first.
+    /// Menus that aren't in any order are left out; ties are sorted by menu ID.
+    pub fn popularity(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<MenuPopularity>> {
+        let mut stmt = conn.prepare(
+            "SELECT menus.id, menus.name, SUM(order_items.quantity) AS total
+            FROM order_items
+            JOIN menus ON menus.id = order_items.menu_id
+            GROUP BY menus.id
+            ORDER BY total DESC, menus.id",
+        )?;
+        let rows = stmt.query_map(params![], |row| {
+            Ok(MenuPopularity {
+                menu_id: row.get(0)?,
+                menu_name: row.get(1)?,
+                total_quantity: row.get(2)?,
+            })
+        })?;
+        rows.collect()
     }
 
     /// Get the ID of an existing menu item by its name
@@ -186,33 +866,124 @@ impl OrderResponse {
         Ok(last_inserted_id)
     }
 
-    /// List all orders
+    /// List all orders, sorted by ID, including orders whose table has been deleted.
+    /// Table names and cooking times come from `order_summary` rather than being recomputed.
     pub fn list(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<OrderResponse>> {
-        let mut stmt = conn.prepare("SELECT orders.id, orders.table_id, t.code FROM orders JOIN tables as t on orders.table_id=t.id")?;
-        let rows = stmt.query_map(params![], |row| {
-            let order_response = OrderResponse {
-                id: row.get(0)?,
-                table_id: row.get(1)?,
-                table_name: row.get(3)?,
-                total_cooking_time: OrderResponse::calculate_total_cooking_time(conn, row.get(0)?)?, // Calculate total cooking time
-                menus: OrderItem::list_all_order_items(conn, row.get(0)?)?,
-            };
-            Ok(order_response)
+        let mut stmt = conn.prepare(&format!("{} ORDER BY s.order_id", ORDER_SUMMARY_SELECT))?;
+        let rows = stmt.query_map(params![], |row| OrderResponse::from_summary_row(conn, row))?;
+
+        // Collect and return the results as a vector
+        rows.collect()
+    }
+
+    /// List one page of orders, sorted by ID like `list`
+    pub fn list_paged(
+        conn: &rusqlite::Connection,
+        limit: u32,
+        offset: u32,
+    ) -> rusqlite::Result<Vec<OrderResponse>> {
+        let mut stmt = conn.prepare(&format!(
+            "{} ORDER BY s.order_id LIMIT ?1 OFFSET ?2",
+            ORDER_SUMMARY_SELECT
+        ))?;
+        let rows = stmt.query_map(params![limit, offset], |row| {
+            OrderResponse::from_summary_row(conn, row)
         })?;
+        // Collect and return the results as a vector
+        rows.collect()
+    }
 
+    /// Get a single order by ID, None when it doesn't exist
+    pub fn get_by_id(
+        conn: &rusqlite::Connection,
+        order_id: i64,
+    ) -> rusqlite::Result<Option<OrderResponse>> {
+        conn.query_row(
+            &format!("{} WHERE orders.id = ?1", ORDER_SUMMARY_SELECT),
+            params![order_id],
+            |row| OrderResponse::from_summary_row(conn, row),
+        )
+        .optional()
+    }
+
+    /// List the largest active orders by the given measure, largest first
+    pub fn list_largest(
+        conn: &rusqlite::Connection,
+        by: OrderSizeMeasure,
+        limit: i64,
+    ) -> rusqlite::Result<Vec<OrderResponse>> {
+        let size = match by {
+            OrderSizeMeasure::Quantity => "SUM(oi.quantity)",
+            OrderSizeMeasure::CookingTime => "SUM(oi.cooking_time)",
+            OrderSizeMeasure::Price => "SUM(oi.quantity * m.price)",
+        };
+        let query = format!(
+            "SELECT orders.id, orders.table_id, t.code, orders.status, orders.created_at
+            FROM orders
+            LEFT JOIN tables as t on orders.table_id=t.id
+            JOIN order_items oi ON oi.order_id = orders.id
+            JOIN menus m ON m.id = oi.menu_id
+            WHERE {}
+            GROUP BY orders.id
+            ORDER BY {} DESC, orders.id
+            LIMIT ?1",
+            ACTIVE_ORDER_FILTER, size
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let rows = stmt.query_map(params![limit], |row| OrderResponse::from_row(conn, row))?;
         // Collect and return the results as a vector
-        Ok(rows.map(|result| result.unwrap()).collect())
+        rows.collect()
     }
 
     /* Utility Functions for Order Model. This block contains utility functions for the Order model */
 
+    /// Build an order response from a row of `ORDER_SUMMARY_SELECT`
+    fn from_summary_row(
+        conn: &rusqlite::Connection,
+        row: &rusqlite::Row,
+    ) -> rusqlite::Result<OrderResponse> {
+        let order_id = row.get(0)?;
+        Ok(OrderResponse {
+            id: order_id,
+            table_id: row.get(1)?,
+            table_name: row.get(2)?,
+            status: row.get(4)?,
+            created_at: row.get(5)?,
+            total_cooking_time: row.get(3)?,
+            total_price: OrderResponse::calculate_total_price(conn, order_id)?,
+            menus: OrderItem::list_all_order_items(conn, order_id)?,
+        })
+    }
+
+    /// Build an order response from a row of
+    /// (orders.id, orders.table_id, tables.code, orders.status, orders.created_at)
+    fn from_row(
+        conn: &rusqlite::Connection,
+        row: &rusqlite::Row,
+    ) -> rusqlite::Result<OrderResponse> {
+        let order_id = row.get(0)?;
+        Ok(OrderResponse {
+            id: order_id,
+            table_id: row.get(1)?,
+            table_name: row.get(2)?,
+            status: row.get(3)?,
+            created_at: row.get(4)?,
+            total_cooking_time: OrderResponse::calculate_total_cooking_time(conn, order_id)?, // Calculate total cooking time
+            total_price: OrderResponse::calculate_total_price(conn, order_id)?,
+            menus: OrderItem::list_all_order_items(conn, order_id)?,
+        })
+    }
+
     /// Get the existing order ID for a specific table, checking if there is an active order
     pub fn get_existing_order_id(
         conn: &Connection,
         table_id: i64,
     ) -> Result<Option<i64>, rusqlite::Error> {
-        let query = "SELECT id FROM orders WHERE table_id = ?1";
-        let mut stmt = conn.prepare(query)?;
+        let query = format!(
+            "SELECT id FROM orders WHERE table_id = ?1 AND {}",
+            ACTIVE_ORDER_FILTER
+        );
+        let mut stmt = conn.prepare(&query)?;
         let mut rows = stmt.query(params![table_id])?;
         if let Some(row) = rows.next()? {
             Ok(Some(row.get(0)?))
@@ -227,7 +998,7 @@ impl OrderResponse {
         order_id: i64,
     ) -> rusqlite::Result<i32> {
         let query = "
-        SELECT SUM(oi.cooking_time)
+        SELECT COALESCE(SUM(oi.cooking_time), 0)
         FROM orders
         JOIN order_items oi ON oi.order_id = orders.id
         WHERE orders.id = ?1
@@ -236,6 +1007,89 @@ impl OrderResponse {
         conn.query_row(query, params![order_id], |row| row.get(0))
     }
 
+    /// Calculate the total price in cents from the current order items and their menus' prices
+    pub fn calculate_total_price(
+        conn: &rusqlite::Connection,
+        order_id: i64,
+    ) -> rusqlite::Result<i64> {
+        let query = "
+        SELECT COALESCE(SUM(m.price * oi.quantity), 0)
+        FROM order_items oi
+        JOIN menus m ON m.id = oi.menu_id
+        WHERE oi.order_id = ?1
+    ";
+
+        conn.query_row(query, params![order_id], |row| row.get(0))
+    }
+
+    /// Set an order's status, returning whether the order exists
+    pub fn set_status(
+        conn: &rusqlite::Connection,
+        order_id: i64,
+        status: &str,
+    ) -> rusqlite::Result<bool> {
+        let updated = conn.execute(
+            "UPDATE orders SET status = ?1 WHERE id = ?2",
+            params![status, order_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Check if an order exists by its ID
+    pub fn exists(conn: &rusqlite::Connection, order_id: i64) -> rusqlite::Result<bool> {
+        let query = "SELECT COUNT(*) FROM orders WHERE id = ?";
+        let count: i64 = conn.query_row(query, params![order_id], |row| row.get(0))?;
+        Ok(count > 0)
+    }
+
+    /// Copy every item of one order, with its quantity and cooking time, into another order
+    pub fn copy_items(
+        conn: &rusqlite::Connection,
+        from_order_id: i64,
+        to_order_id: i64,
+    ) -> rusqlite::Result<usize> {
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, unit_cooking_time, cooking_time, quantity)
+            SELECT ?2, menu_id, unit_cooking_time, cooking_time, quantity FROM order_items
+            WHERE order_id = ?1
+            ORDER BY id",
+            params![from_order_id, to_order_id],
+        )
+    }
+
+    /// Delete an order along with its items, returning whether the order existed.
+    /// Run it in a transaction so the items aren't removed without the order.
+    pub fn delete(conn: &rusqlite::Connection, order_id: i64) -> rusqlite::Result<bool> {
+        conn.execute(
+            "DELETE FROM order_items WHERE order_id = ?1",
+            params![order_id],
+        )?;
+        let deleted = conn.execute("DELETE FROM orders WHERE id = ?1", params![order_id])?;
+        Ok(deleted > 0)
+    }
+
+    /// Delete a table's closed order along with its items, so the table can take a new order.
+    /// Run it in a transaction with whatever takes the table next.
+    pub fn release_closed_order(
+        conn: &rusqlite::Connection,
+        table_id: i64,
+    ) -> rusqlite::Result<()> {
+        let closed_order_id: Option<i64> = conn
+            .query_row(
+                &format!(
+                    "SELECT id FROM orders WHERE table_id = ?1 AND NOT ({})",
+                    ACTIVE_ORDER_FILTER
+                ),
+                params![table_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(order_id) = closed_order_id {
+            OrderResponse::delete(conn, order_id)?;
+        }
+        Ok(())
+    }
+
     /// Check if the order has any remaining items
     pub fn has_items(conn: &rusqlite::Connection, order_id: i64) -> rusqlite::Result<bool> {
         let query = "SELECT COUNT(*) FROM order_items WHERE order_id = ?";
@@ -244,93 +1098,306 @@ impl OrderResponse {
     }
 }
 
+/// Functions for reading KitchenLoad records
+impl KitchenLoad {
+    /// List the total cooking time of each table's order still to be cooked, most loaded first.
+    /// Tables without an order, or whose order is served or cancelled, are left out.
+    pub fn list(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<KitchenLoad>> {
+        let mut stmt = conn.prepare(
+            "SELECT tables.id, tables.code, SUM(order_items.cooking_time) AS total
+            FROM tables
+            JOIN orders ON orders.table_id = tables.id
+            JOIN order_items ON order_items.order_id = orders.id
+            WHERE orders.status NOT IN ('served', 'cancelled')
+            GROUP BY tables.id
+            ORDER BY total DESC, tables.code",
+        )?;
+        let rows = stmt.query_map(params![], |row| {
+            Ok(KitchenLoad {
+                table_id: row.get(0)?,
+                table_code: row.get(1)?,
+                total_cooking_time: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+/// Functions for building Receipt records
+impl Receipt {
+    /// Build the receipt of a table's active order with tax at `tax_rate`, None when the table has
+    /// none. Served orders still get one, since they're yet to be paid.
+    /// Lines are listed in the order their items were added and priced at the menus' current
+    /// prices, so a price change after ordering changes an open bill.
+    pub fn for_table(
+        conn: &rusqlite::Connection,
+        table_id: i64,
+        tax_rate: f64,
+    ) -> rusqlite::Result<Option<Receipt>> {
+        let order = conn
+            .query_row(
+                &format!(
+                    "SELECT orders.id, tables.code FROM orders
+                    JOIN tables ON tables.id = orders.table_id
+                    WHERE orders.table_id = ?1 AND {}",
+                    ACTIVE_ORDER_FILTER
+                ),
+                params![table_id],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )
+            .optional()?;
+        let Some((order_id, table_code)) = order else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT m.name, oi.quantity, m.price, m.price * oi.quantity
+            FROM order_items oi
+            JOIN menus m ON m.id = oi.menu_id
+            WHERE oi.order_id = ?1
+            ORDER BY oi.id",
+        )?;
+        let items = stmt
+            .query_map(params![order_id], |row| {
+                Ok(ReceiptLine {
+                    menu_name: row.get(0)?,
+                    quantity: row.get(1)?,
+                    unit_price: row.get(2)?,
+                    line_total: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let subtotal: i64 = items.iter().map(|item| item.line_total).sum();
+        let tax = (subtotal as f64 * tax_rate).round() as i64;
+        Ok(Some(Receipt {
+            table_code,
+            items,
+            subtotal,
+            tax,
+            total: subtotal + tax,
+        }))
+    }
+}
+
+/// Functions for managing IdempotentResponse records
+impl IdempotentResponse {
+    /// Get the response stored for `key`, unless it was stored more than `ttl_secs` ago
+    pub fn get(
+        conn: &rusqlite::Connection,
+        key: &str,
+        ttl_secs: u64,
+    ) -> rusqlite::Result<Option<IdempotentResponse>> {
+        conn.query_row(
+            "SELECT request, status, location, body FROM idempotency_keys
+            WHERE key = ?1 AND created_at > CAST(strftime('%s', 'now') AS INTEGER) - ?2",
+            params![key, ttl_secs as i64],
+            |row| {
+                Ok(IdempotentResponse {
+                    request: row.get(0)?,
+                    status: row.get(1)?,
+                    location: row.get(2)?,
+                    body: row.get(3)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    /// Store the response for `key`, first dropping every key older than `ttl_secs`
+    pub fn store(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        ttl_secs: u64,
+    ) -> rusqlite::Result<()> {
+        conn.execute(
+            "DELETE FROM idempotency_keys WHERE created_at <= CAST(strftime('%s', 'now') AS INTEGER) - ?1",
+            params![ttl_secs as i64],
+        )?;
+        conn.execute(
+            "INSERT OR REPLACE INTO idempotency_keys (key, request, status, location, body) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![key, self.request, self.status, self.location, self.body],
+        )?;
+        Ok(())
+    }
+}
+
+/// Functions for reading OrderSummary records
+impl OrderSummary {
+    /// List the summaries of all orders, sorted by order ID
+    pub fn list(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<OrderSummary>> {
+        let mut stmt = conn.prepare(
+            "SELECT order_id, table_code, total_cooking_time, item_count, total_quantity
+            FROM order_summary ORDER BY order_id",
+        )?;
+        let rows = stmt.query_map(params![], |row| {
+            Ok(OrderSummary {
+                order_id: row.get(0)?,
+                table_code: row.get(1)?,
+                total_cooking_time: row.get(2)?,
+                item_count: row.get(3)?,
+                total_quantity: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+}
+
+/// Functions for reading OrderItemResponse rows
+impl OrderItemResponse {
+    /// Map a row selected with `ORDER_ITEM_COLUMNS` by column name, so the SELECT order doesn't matter
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<OrderItemResponse> {
+        Ok(OrderItemResponse {
+            id: row.get("id")?,
+            order_id: row.get("order_id")?,
+            menu_id: row.get("menu_id")?,
+            menu_name: row.get("menu_name")?,
+            quantity: row.get("quantity")?,
+            cooking_time: row.get("cooking_time")?,
+            price: row.get("price")?,
+            served: row.get("served")?,
+        })
+    }
+}
+
 /// Functions for managing OrderItem records
 impl OrderItem {
-    /// Create a new order item
+    /// Create a new order item of `quantity` units, each taking `unit_cooking_time`
     pub fn create(
         conn: &rusqlite::Connection,
         order_id: i64,
         menu_id: i64,
-        cooking_time: i64,
+        unit_cooking_time: i64,
+        quantity: i64,
     ) -> rusqlite::Result<i64> {
         conn.execute(
-            "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4)",
-            params![order_id, menu_id, cooking_time, 1],
+            "INSERT INTO order_items (order_id, menu_id, unit_cooking_time, cooking_time, quantity) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                order_id,
+                menu_id,
+                unit_cooking_time,
+                unit_cooking_time * quantity,
+                quantity
+            ],
         )?;
         // Retrieve the ID of the last inserted row
         let last_inserted_id = conn.last_insert_rowid();
         Ok(last_inserted_id)
     }
 
-    /// List all order items for a specific order
+    /// List all order items for a specific order, in the order they were added
     pub fn list_all_order_items(
         conn: &rusqlite::Connection,
         order_id: i64,
     ) -> rusqlite::Result<Vec<OrderItemResponse>> {
-        let mut stmt = conn.prepare("SELECT order_items.id, order_items.order_id, order_items.menu_id, m.name, order_items.quantity, order_items.cooking_time FROM order_items JOIN menus as m on order_items.menu_id=m.id WHERE order_id = ?1")?;
-        let rows = stmt.query_map(params![order_id], |row| {
-            Ok(OrderItemResponse {
-                id: row.get(0)?,
-                order_id: row.get(1)?,
-                menu_id: row.get(2)?,
-                menu_name: row.get(3)?,
-                quantity: row.get(4)?,
-                cooking_time: row.get(5)?,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM order_items JOIN menus as m on order_items.menu_id=m.id WHERE order_id = ?1 ORDER BY order_items.id",
+            ORDER_ITEM_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![order_id], OrderItemResponse::from_row)?;
         // Collect and return the results as a vector
         let result: Result<Vec<_>, _> = rows.collect();
         result
     }
 
-    /// List all order items for a specific table
+    /// List all order items for a specific table, in the order they were added
     pub fn list_order_items(
         conn: &rusqlite::Connection,
         table_id: i64,
     ) -> rusqlite::Result<Vec<OrderItemResponse>> {
-        let query = "SELECT order_items.id, order_items.order_id, order_items.menu_id, m.name, order_items.quantity, order_items.cooking_time
-        FROM order_items
-        JOIN orders ON orders.id = order_items.order_id
-        JOIN menus as m on order_items.menu_id=m.id
-        WHERE orders.table_id = ?1";
-        let mut stmt = conn.prepare(query)?;
-        let rows = stmt.query_map(params![table_id], |row| {
-            Ok(OrderItemResponse {
-                id: row.get(0)?,
-                order_id: row.get(1)?,
-                menu_id: row.get(2)?,
-                menu_name: row.get(3)?,
-                quantity: row.get(4)?,
-                cooking_time: row.get(5)?,
-            })
-        })?;
+        let query = format!(
+            "SELECT {}
+            FROM order_items
+            JOIN orders ON orders.id = order_items.order_id
+            JOIN menus as m on order_items.menu_id=m.id
+            WHERE orders.table_id = ?1 AND {}
+            ORDER BY order_items.id",
+            ORDER_ITEM_COLUMNS, ACTIVE_ORDER_FILTER
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let rows = stmt.query_map(params![table_id], OrderItemResponse::from_row)?;
         // Collect and return the results as a vector
         let result: Result<Vec<_>, _> = rows.collect();
         result
     }
 
+    /// List the order items of several tables at once, keyed by table ID.
+    /// Every requested table has an entry, empty when it has no items.
+    pub fn list_order_items_for_tables(
+        conn: &rusqlite::Connection,
+        table_ids: &BTreeSet<i64>,
+    ) -> rusqlite::Result<BTreeMap<i64, Vec<OrderItemResponse>>> {
+        let placeholders = vec!["?"; table_ids.len()].join(", ");
+        let query = format!(
+            "SELECT orders.table_id AS table_id, {}
+            FROM order_items
+            JOIN orders ON orders.id = order_items.order_id
+            JOIN menus as m on order_items.menu_id=m.id
+            WHERE orders.table_id IN ({}) AND {}
+            ORDER BY order_items.id",
+            ORDER_ITEM_COLUMNS, placeholders, ACTIVE_ORDER_FILTER
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let rows = stmt.query_map(params_from_iter(table_ids), |row| {
+            Ok((
+                row.get::<_, i64>("table_id")?,
+                OrderItemResponse::from_row(row)?,
+            ))
+        })?;
+        // Group the items by table, starting every requested table with an empty list
+        let mut items_by_table: BTreeMap<i64, Vec<OrderItemResponse>> = table_ids
+            .iter()
+            .map(|table_id| (*table_id, vec![]))
+            .collect();
+        for row in rows {
+            let (table_id, item) = row?;
+            items_by_table.entry(table_id).or_default().push(item);
+        }
+        Ok(items_by_table)
+    }
+
     /// Get a specific item from a specific table by menu ID
     pub fn get_item(
         conn: &rusqlite::Connection,
         table_id: i64,
         menu_id: i64,
     ) -> rusqlite::Result<Option<OrderItemResponse>> {
-        let query = "
-        SELECT order_items.id, order_items.order_id, order_items.menu_id, m.name, order_items.quantity, order_items.cooking_time
-        FROM order_items
-        JOIN orders ON orders.id = order_items.order_id
-        JOIN menus as m on order_items.menu_id=m.id
-        WHERE orders.table_id = ?1 AND order_items.menu_id = ?2";
-        let mut stmt = conn.prepare(query)?;
+        let query = format!(
+            "SELECT {}
+            FROM order_items
+            JOIN orders ON orders.id = order_items.order_id
+            JOIN menus as m on order_items.menu_id=m.id
+            WHERE orders.table_id = ?1 AND order_items.menu_id = ?2 AND {}",
+            ORDER_ITEM_COLUMNS, ACTIVE_ORDER_FILTER
+        );
+        let mut stmt = conn.prepare(&query)?;
         let result = stmt.query_row(params![table_id, menu_id], |row| {
-            Ok(OrderItemResponse {
-                id: row.get(0)?,
-                order_id: row.get(1)?,
-                menu_id: row.get(2)?,
-                menu_name: row.get(3)?,
-                quantity: row.get(4)?,
-                cooking_time: row.get(5)?,
-            })
+            OrderItemResponse::from_row(row)
+        });
+        // Return the result if found, otherwise handle the error
+        match result {
+            Ok(item) => Ok(Some(item)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Get a specific item from a specific order by menu ID
+    pub fn get_by_order(
+        conn: &rusqlite::Connection,
+        order_id: i64,
+        menu_id: i64,
+    ) -> rusqlite::Result<Option<OrderItemResponse>> {
+        let query = format!(
+            "SELECT {}
+            FROM order_items
+            JOIN menus as m on order_items.menu_id=m.id
+            WHERE order_items.order_id = ?1 AND order_items.menu_id = ?2",
+            ORDER_ITEM_COLUMNS
+        );
+        let mut stmt = conn.prepare(&query)?;
+        let result = stmt.query_row(params![order_id, menu_id], |row| {
+            OrderItemResponse::from_row(row)
         });
         // Return the result if found, otherwise handle the error
         match result {
@@ -358,16 +1425,119 @@ impl OrderItem {
         }
     }
 
-    /// Increase the quantity of an existing order item
+    /// Set the quantity of an order's item, scaling its cooking time to match.
+    /// Raising the quantity leaves the item no longer served.
+    /// Returns whether the order has an item for the menu.
+    pub fn set_quantity(
+        conn: &rusqlite::Connection,
+        order_id: i64,
+        menu_id: i64,
+        quantity: i64,
+    ) -> rusqlite::Result<bool> {
+        let updated = conn.execute(
+            "UPDATE order_items
+            SET cooking_time = unit_cooking_time * ?3, served = served AND ?3 <= quantity, quantity = ?3
+            WHERE order_id = ?1 AND menu_id = ?2",
+            params![order_id, menu_id, quantity],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Mark the item of a table's order for a menu as delivered.
+    /// Returns whether the table's order has an item for the menu.
+    pub fn mark_served(
+        conn: &rusqlite::Connection,
+        table_id: i64,
+        menu_id: i64,
+    ) -> rusqlite::Result<bool> {
+        let updated = conn.execute(
+            &format!(
+                "UPDATE order_items SET served = 1
+                WHERE menu_id = ?2 AND order_id IN (SELECT id FROM orders WHERE table_id = ?1 AND {})",
+                ACTIVE_ORDER_FILTER
+            ),
+            params![table_id, menu_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Increase the quantity of an existing order item by `quantity`.
+    /// Added units still have to be delivered, so the item is no longer served.
     pub fn add_quantity_of_existing_order_item(
         conn: &Connection,
         order_item_id: i64,
+        quantity: i64,
     ) -> Result<bool, rusqlite::Error> {
         let query = "UPDATE order_items
-        SET cooking_time = (cooking_time / quantity) * (quantity + 1),
-        quantity = quantity + 1
+        SET cooking_time = unit_cooking_time * (quantity + ?2),
+        quantity = quantity + ?2,
+        served = served AND ?2 <= 0
         WHERE id = ?1";
-        let result = conn.execute(query, params![order_item_id])?;
+        let result = conn.execute(query, params![order_item_id, quantity])?;
         Ok(result > 0)
     }
 }
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::{setup_static_data, setup_test_db};
+    use serde_json::json;
+
+    // Test Case: An order item round-trips through JSON with a `cooking_time` field
+    #[test]
+    fn test_order_item_json_round_trip() {
+        let item = OrderItem {
+            id: 0,
+            order_id: 1,
+            menu_id: 2,
+            cooking_time: 5,
+        };
+        let value = serde_json::to_value(&item).expect("Failed to serialize order item");
+        assert_eq!(
+            value,
+            json!({"order_id": 1, "menu_id": 2, "cooking_time": 5})
+        );
+        let parsed: OrderItem =
+            serde_json::from_value(value).expect("Failed to deserialize order item");
+        assert_eq!(
+            (parsed.order_id, parsed.menu_id, parsed.cooking_time),
+            (1, 2, 5)
+        );
+    }
+
+    // Test Case: Order item rows are mapped by column name, whatever order the SELECT lists them in
+    #[test]
+    fn test_order_item_columns_mapped_by_name() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (1)", [])
+            .expect("Order creation failed");
+        let order_id = conn.last_insert_rowid();
+        OrderItem::create(&conn, order_id, 2, 4, 3).expect("OrderItems creation failed");
+
+        let items = OrderItem::list_all_order_items(&conn, order_id).expect("Failed to list items");
+        assert_eq!(items.len(), 1);
+        assert_eq!((items[0].quantity, items[0].cooking_time), (3, 12));
+
+        // The same columns listed back to front map to the same values
+        let mut columns: Vec<_> = ORDER_ITEM_COLUMNS.split(", ").collect();
+        columns.reverse();
+        let reversed = columns.join(", ");
+        let item = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM order_items JOIN menus as m on order_items.menu_id=m.id WHERE order_id = ?1",
+                    reversed
+                ),
+                [order_id],
+                OrderItemResponse::from_row,
+            )
+            .expect("Failed to read item");
+        assert_eq!(
+            serde_json::to_value(&item).unwrap(),
+            serde_json::to_value(&items[0]).unwrap()
+        );
+    }
+}
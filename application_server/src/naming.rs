@@ -0,0 +1,121 @@
+// src/naming.rs
+use serde_json::{Map, Value};
+
+/// Naming convention of JSON field names, selected by the API version prefix.
+/// Handlers always work in snake_case; `/v2` requests and responses are translated to camelCase.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldCase {
+    Snake,
+    Camel,
+}
+
+impl FieldCase {
+    /// Field case used by a request for `path`: camelCase under `/v2`, snake_case otherwise
+    pub fn of_path(path: &str) -> FieldCase {
+        if path == "/v2" || path.starts_with("/v2/") {
+            FieldCase::Camel
+        } else {
+            FieldCase::Snake
+        }
+    }
+}
+
+/// Convert a snake_case name to camelCase, e.g. `table_name` to `tableName`
+pub fn to_camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper_next = false;
+    for c in name.chars() {
+        if c == '_' && !camel.is_empty() {
+            upper_next = true;
+        } else if upper_next {
+            camel.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+
+/// Convert a camelCase name to snake_case, e.g. `tableName` to `table_name`
+pub fn to_snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for c in name.chars() {
+        if c.is_uppercase() {
+            if !snake.is_empty() {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Rename the keys of every object in `value`, recursively.
+/// With `rename_top_level` off, the keys of a top-level object are kept as they are,
+/// for responses keyed by data such as menu names rather than by field names.
+pub fn rename_keys(value: Value, rename: fn(&str) -> String, rename_top_level: bool) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = if rename_top_level { rename(&key) } else { key };
+                    (key, rename_keys(value, rename, true))
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| rename_keys(item, rename, true))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Test Case: Names convert between snake_case and camelCase in both directions
+    #[test]
+    fn test_case_conversion() {
+        for (snake, camel) in [
+            ("id", "id"),
+            ("table_name", "tableName"),
+            ("average_cooking_time", "averageCookingTime"),
+        ] {
+            assert_eq!(to_camel_case(snake), camel);
+            assert_eq!(to_snake_case(camel), snake);
+        }
+    }
+
+    // Test Case: Nested keys are renamed and top-level data keys can be left alone
+    #[test]
+    fn test_rename_keys() {
+        let value = json!({"menu_id": 1, "order_items": [{"cooking_time": 5}]});
+        assert_eq!(
+            rename_keys(value.clone(), to_camel_case, true),
+            json!({"menuId": 1, "orderItems": [{"cookingTime": 5}]})
+        );
+        assert_eq!(
+            rename_keys(value, to_camel_case, false),
+            json!({"menu_id": 1, "order_items": [{"cookingTime": 5}]})
+        );
+    }
+
+    // Test Case: The /v2 prefix selects camelCase
+    #[test]
+    fn test_field_case_of_path() {
+        assert_eq!(FieldCase::of_path("/v2/orders"), FieldCase::Camel);
+        assert_eq!(FieldCase::of_path("/v1/orders"), FieldCase::Snake);
+        assert_eq!(FieldCase::of_path("/orders/v2"), FieldCase::Snake);
+        assert_eq!(FieldCase::of_path("/v20/orders"), FieldCase::Snake);
+    }
+}
@@ -1,153 +1,3269 @@
 // src/routes.rs
 
-use crate::db::get_db_conn;
+use crate::cache::MenuCache;
+use crate::config::Config;
+use crate::db::{init_pool, DbConn, DbPool};
+use crate::error::ApiError;
+use crate::events::OrderEvents;
 use crate::handlers::{
-    create_menu_handler, create_order_handler, create_table_handler, delete_order_item_handler,
-    get_order_item_for_table_handler, list_menu_handler, list_order_handler,
-    list_order_items_for_table_handler, list_table_handler,
+    clear_order_handler, copy_order_handler, create_menu_handler, create_order_handler,
+    create_table_handler, create_tables_bulk_handler, deep_health_handler, delete_menu_handler,
+    delete_order_handler, delete_order_item_handler, delete_table_handler, dump_sql_handler,
+    ensure_menus_handler, get_menu_handler, get_menu_stats_handler, get_order_handler,
+    get_order_item_for_order_handler, get_order_item_for_table_handler, get_state_handler,
+    get_table_handler, health_handler, info_handler, kitchen_load_handler,
+    list_largest_orders_handler, list_menu_handler, list_order_handler,
+    list_order_items_for_order_handler, list_order_items_for_table_handler,
+    list_order_items_for_tables_handler, list_order_summaries_handler, list_table_handler,
+    list_tables_by_load_handler, mark_item_served_handler, menu_popularity_handler,
+    occupied_tables_handler, order_receipt_handler, reorder_menus_handler, search_menus_handler,
+    set_item_quantity_handler, set_menu_cooking_time_handler, update_menu_handler,
+    update_order_status_handler, update_table_handler, validate_order_handler, ws_orders_handler,
 };
-use rusqlite::Connection;
+use crate::locks::TableLocks;
+use crate::naming::{rename_keys, to_camel_case, to_snake_case, FieldCase};
+use rand::Rng;
+use serde::de::DeserializeOwned;
 use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use warp::filters::path::FullPath;
+use warp::http::Method;
+use warp::hyper::body::Bytes;
 use warp::{Filter, Rejection, Reply};
 
-/// Middleware for handling errors and converting them into JSON responses
-/// Handles Route Not Found and Deserialization Errors.
+/// Request headers that may appear in body logs; anything else (e.g. `Authorization`) is left out
+const LOGGED_HEADERS: [&str; 4] = ["content-type", "content-length", "accept", "user-agent"];
+
+/// Longest body, in bytes, written to the body logs
+const MAX_LOGGED_BODY_BYTES: usize = 1024;
+
+/// Largest body, in bytes, accepted by the create routes; bigger ones get a 413
+const MAX_CREATE_BODY_BYTES: u64 = 16 * 1024;
+
+/// Rejection for a request body that is not valid JSON for the route
+#[derive(Debug)]
+struct InvalidBody;
+
+impl warp::reject::Reject for InvalidBody {}
+
+/// Rejection for a failure injected by the chaos filter
+#[derive(Debug)]
+struct ChaosError;
+
+impl warp::reject::Reject for ChaosError {}
+
+/// Rejection for a request whose `Accept` header excludes JSON
+#[derive(Debug)]
+struct NotAcceptable;
+
+impl warp::reject::Reject for NotAcceptable {}
+
+/// Rejection for a request turned away because `MAX_CONCURRENT_REQUESTS` are already in flight
+/// or no pooled database connection became free in time
+#[derive(Debug)]
+struct ServerBusy;
+
+impl warp::reject::Reject for ServerBusy {}
+
+/// Rejection for a request needing the database when no connection to it could be opened
+#[derive(Debug)]
+struct DatabaseUnavailable;
+
+impl warp::reject::Reject for DatabaseUnavailable {}
+
+/// Seconds a client turned away as busy is asked to wait before retrying
+const BUSY_RETRY_AFTER_SECS: u64 = 1;
+
+/// Routes whose JSON response is an object keyed by data, such as menu names, which `/v2` must not rename
+const DATA_KEYED_PATHS: [&str; 1] = ["/menus/ensure"];
 
-async fn handle_rejection(err: Rejection) -> Result<impl Reply, Rejection> {
+/// Methods a POST request is allowed to be overridden to via `X-HTTP-Method-Override`
+const METHOD_OVERRIDE_ALLOWLIST: [Method; 2] = [Method::DELETE, Method::PATCH];
+
+/// Middleware for handling errors and converting them into JSON responses
+/// Handles handler errors, Route Not Found and Deserialization Errors.
+async fn handle_rejection(err: Rejection) -> Result<warp::reply::Response, Rejection> {
+    // Handle errors reported by the handlers
+    if let Some(api_error) = err.find::<ApiError>() {
+        Ok(warp::reply::with_status(
+            warp::reply::json(
+                &serde_json::json!({"error": api_error.message(), "code": api_error.code()}),
+            ),
+            api_error.status(),
+        )
+        .into_response())
+    // Handle requests turned away by the concurrency limit
+    } else if err.find::<ServerBusy>().is_some() {
+        Ok(warp::reply::with_header(
+            warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({"error":"Server busy"})),
+                warp::http::StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            "Retry-After",
+            BUSY_RETRY_AFTER_SECS.to_string(),
+        )
+        .into_response())
+    // Handle requests needing a database that can't be opened
+    } else if err.find::<DatabaseUnavailable>().is_some() {
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error":"Database unavailable"})),
+            warp::http::StatusCode::SERVICE_UNAVAILABLE,
+        )
+        .into_response())
     // Handle route not found error
-    if err.is_not_found() {
+    } else if err.is_not_found() {
         Ok(warp::reply::with_status(
             warp::reply::json(&format!("Error: Resource not found {:?}", err)),
             warp::http::StatusCode::NOT_FOUND,
-        ))
+        )
+        .into_response())
     // Handle deserialization error
-    } else if let Some(_) = err.find::<warp::filters::body::BodyDeserializeError>() {
+    } else if err
+        .find::<warp::filters::body::BodyDeserializeError>()
+        .is_some()
+        || err.find::<InvalidBody>().is_some()
+    {
         Ok(warp::reply::with_status(
-            warp::reply::json(&format!("Error: Failed to deserialize request body")),
+            warp::reply::json(&"Error: Failed to deserialize request body"),
             warp::http::StatusCode::BAD_REQUEST,
-        ))
-    // Handle other errors
+        )
+        .into_response())
+    // Handle bodies over the size limit of the route
+    } else if err.find::<warp::reject::PayloadTooLarge>().is_some() {
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error":"Payload too large"})),
+            warp::http::StatusCode::PAYLOAD_TOO_LARGE,
+        )
+        .into_response())
+    // Handle size-limited routes called without a Content-Length header
+    } else if err.find::<warp::reject::LengthRequired>().is_some() {
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error":"Content-Length required"})),
+            warp::http::StatusCode::LENGTH_REQUIRED,
+        )
+        .into_response())
+    // Handle invalid query string error
+    } else if err.find::<warp::reject::InvalidQuery>().is_some() {
+        Ok(warp::reply::with_status(
+            warp::reply::json(&"Error: Failed to parse query parameters"),
+            warp::http::StatusCode::BAD_REQUEST,
+        )
+        .into_response())
+    // Handle clients that don't accept JSON responses
+    } else if err.find::<NotAcceptable>().is_some() {
+        Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error":"Only application/json is supported"})),
+            warp::http::StatusCode::NOT_ACCEPTABLE,
+        )
+        .into_response())
+    // Handle failures injected for chaos testing
+    } else if err.find::<ChaosError>().is_some() {
+        Ok(warp::reply::with_status(
+            warp::reply::json(&"Error: Injected failure for chaos testing"),
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )
+        .into_response())
+    // Handle other errors, logging the details under an ID the client can quote instead of sending them
     } else {
+        let request_id = format!("{:016x}", rand::random::<u64>());
+        tracing::error!(request_id = request_id.as_str(), rejection = ?err, "unhandled rejection");
         Ok(warp::reply::with_status(
-            warp::reply::json(&format!("Error: {:?}", err)),
+            warp::reply::json(
+                &serde_json::json!({"error":"Internal server error","request_id":request_id}),
+            ),
             warp::http::StatusCode::INTERNAL_SERVER_ERROR,
-        ))
+        )
+        .into_response())
     }
 }
 
 /// Helper function to provide a database connection to route handlers
-/// Supplies a new database connection for each route
-fn with_db() -> impl Filter<Extract = (Connection,), Error = Infallible> + Clone {
-    warp::any().map(|| get_db_conn())
+/// Checks a connection out of the shared pool for each route. Waiting for a free connection
+/// blocks, so it happens off the async workers; requests that time out are turned away as busy,
+/// or as unavailable when the pool couldn't open a single connection to the database.
+fn with_db(pool: DbPool) -> impl Filter<Extract = (DbConn,), Error = Rejection> + Clone {
+    warp::any().and_then(move || {
+        let pool = pool.clone();
+        async move {
+            let checkout_pool = pool.clone();
+            match tokio::task::spawn_blocking(move || checkout_pool.get()).await {
+                Ok(Ok(conn)) => Ok(conn),
+                Ok(Err(err)) if pool.state().connections == 0 => {
+                    tracing::error!(error = %err, "database unavailable");
+                    Err(warp::reject::custom(DatabaseUnavailable))
+                }
+                Ok(Err(err)) => {
+                    tracing::error!(error = %err, "no database connection available");
+                    Err(warp::reject::custom(ServerBusy))
+                }
+                Err(err) => {
+                    tracing::error!(error = %err, "database connection checkout failed");
+                    Err(warp::reject::custom(ServerBusy))
+                }
+            }
+        }
+    })
 }
 
-/// Route to list all orders. GET request
-pub fn list_all_orders_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+/// Helper function to provide the shared configuration to route handlers and filters
+fn with_config(
+    config: Arc<Config>,
+) -> impl Filter<Extract = (Arc<Config>,), Error = Infallible> + Clone {
+    warp::any().map(move || config.clone())
+}
+
+/// Render a body for the logs, truncated to `MAX_LOGGED_BODY_BYTES`
+fn truncate_body(body: &[u8]) -> String {
+    let shown = &body[..body.len().min(MAX_LOGGED_BODY_BYTES)];
+    let mut text = String::from_utf8_lossy(shown).into_owned();
+    if body.len() > MAX_LOGGED_BODY_BYTES {
+        text.push_str(&format!("... ({} bytes total)", body.len()));
+    }
+    text
+}
+
+/// Filter deserializing a JSON request body, logging it first when `LOG_BODIES` is on.
+/// Under `/v2` the body's fields are expected in camelCase.
+fn json_body<T: DeserializeOwned + Send + 'static>(
+    config: Arc<Config>,
+) -> impl Filter<Extract = (T,), Error = Rejection> + Clone {
+    warp::path::full()
+        .and(warp::body::bytes())
+        .and_then(move |path: FullPath, body: Bytes| {
+            let log_bodies = config.log_bodies;
+            async move {
+                if log_bodies {
+                    tracing::info!(path = path.as_str(), body = %truncate_body(&body), "request body");
+                }
+                match FieldCase::of_path(path.as_str()) {
+                    FieldCase::Snake => serde_json::from_slice(&body),
+                    // Translate camelCase fields back to the snake_case the handlers expect
+                    FieldCase::Camel => serde_json::from_slice(&body).and_then(|value| {
+                        serde_json::from_value(rename_keys(value, to_snake_case, true))
+                    }),
+                }
+                .map_err(|_| warp::reject::custom(InvalidBody))
+            }
+        })
+}
+
+/// Filter consuming an optional `/v1` or `/v2` prefix and extracting the field case it selects.
+/// Unprefixed paths behave like `/v1`.
+fn api_version() -> impl Filter<Extract = (FieldCase,), Error = Infallible> + Clone {
+    warp::path("v2")
+        .map(|| FieldCase::Camel)
+        .or(warp::path("v1").map(|| FieldCase::Snake))
+        .unify()
+        .or(warp::any().map(|| FieldCase::Snake))
+        .unify()
+}
+
+/// Filter rejecting requests for a `DISABLED_ROUTES` path as not found, before any route sees them.
+/// It runs after the API version prefix is consumed, so a disabled path is disabled in every version.
+fn route_enabled(config: Arc<Config>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::path::peek()
+        .and_then(move |path: warp::path::Peek| {
+            let disabled = config.route_disabled(&format!("/{}", path.as_str()));
+            async move {
+                if disabled {
+                    Err(warp::reject::not_found())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Rename the fields of a JSON response to camelCase for `/v2` requests.
+/// Responses keyed by data rather than field names keep their top-level keys.
+async fn rename_response_fields(
+    field_case: FieldCase,
+    path: FullPath,
+    reply: impl Reply,
+) -> Result<warp::reply::Response, Rejection> {
+    let resp = reply.into_response();
+    if field_case == FieldCase::Snake {
+        return Ok(resp);
+    }
+    let rename_top_level = !DATA_KEYED_PATHS
+        .iter()
+        .any(|data_keyed| path.as_str().ends_with(data_keyed));
+    let (parts, body) = resp.into_parts();
+    let bytes = warp::hyper::body::to_bytes(body).await.unwrap_or_default();
+    // Bodies that aren't JSON are sent as they are
+    let bytes = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .and_then(|value| serde_json::to_vec(&rename_keys(value, to_camel_case, rename_top_level)))
+        .map(Bytes::from)
+        .unwrap_or(bytes);
+    Ok(warp::reply::Response::from_parts(parts, bytes.into()))
+}
+
+/// Re-serialize JSON error bodies with indentation when `PRETTY_ERRORS` is on.
+/// Success bodies, and everything when it is off, pass through untouched.
+async fn pretty_error_body(
+    reply: impl Reply,
+    config: Arc<Config>,
+) -> Result<warp::reply::Response, Rejection> {
+    let resp = reply.into_response();
+    let status = resp.status();
+    if !config.pretty_errors || !(status.is_client_error() || status.is_server_error()) {
+        return Ok(resp);
+    }
+    let (parts, body) = resp.into_parts();
+    let bytes = warp::hyper::body::to_bytes(body).await.unwrap_or_default();
+    // Bodies that aren't JSON are sent as they are
+    let bytes = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .and_then(|value| serde_json::to_vec_pretty(&value))
+        .map(Bytes::from)
+        .unwrap_or(bytes);
+    Ok(warp::reply::Response::from_parts(parts, bytes.into()))
+}
+
+/// Log a response body when `LOG_BODIES` is on, rebuilding the response around the read bytes.
+/// Responses pass through untouched when it is off.
+async fn log_response_body(
+    reply: impl Reply,
+    config: Arc<Config>,
+) -> Result<warp::reply::Response, Rejection> {
+    let resp = reply.into_response();
+    if !config.log_bodies {
+        return Ok(resp);
+    }
+    let (parts, body) = resp.into_parts();
+    let bytes = warp::hyper::body::to_bytes(body).await.unwrap_or_default();
+    tracing::info!(status = parts.status.as_u16(), body = %truncate_body(&bytes), "response body");
+    Ok(warp::reply::Response::from_parts(parts, bytes.into()))
+}
+
+/// Helper function to provide the shared menu cache to the menu handlers
+fn with_menu_cache(
+    menu_cache: Arc<MenuCache>,
+) -> impl Filter<Extract = (Arc<MenuCache>,), Error = Infallible> + Clone {
+    warp::any().map(move || menu_cache.clone())
+}
+
+/// Helper function to provide the shared per-table locks to the order handlers
+fn with_table_locks(
+    table_locks: Arc<TableLocks>,
+) -> impl Filter<Extract = (Arc<TableLocks>,), Error = Infallible> + Clone {
+    warp::any().map(move || table_locks.clone())
+}
+
+/// Helper function to provide the shared order event channel to the order handlers
+fn with_order_events(
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = (Arc<OrderEvents>,), Error = Infallible> + Clone {
+    warp::any().map(move || order_events.clone())
+}
+
+/// Filter injecting artificial latency and random 500s to exercise client retry/backoff.
+/// For testing only: it is a no-op unless `CHAOS_DELAY_MS` or `CHAOS_ERROR_RATE` is configured.
+fn chaos(config: Arc<Config>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::any()
+        .and_then(move || {
+            let config = config.clone();
+            async move {
+                if config.chaos_delay_ms > 0 {
+                    tokio::time::sleep(Duration::from_millis(config.chaos_delay_ms)).await;
+                }
+                if config.chaos_error_rate > 0.0
+                    && rand::thread_rng().gen_bool(config.chaos_error_rate)
+                {
+                    return Err(warp::reject::custom(ChaosError));
+                }
+                Ok(())
+            }
+        })
+        .untuple_one()
+}
+
+/// Filter holding a permit from the request semaphore while the rest of the request is handled.
+/// Requests that find no permit free are rejected as busy instead of queueing;
+/// without a semaphore (`MAX_CONCURRENT_REQUESTS` is 0) every request passes.
+fn limit_concurrency(
+    semaphore: Option<Arc<Semaphore>>,
+) -> impl Filter<Extract = (Option<OwnedSemaphorePermit>,), Error = Rejection> + Clone {
+    warp::any().and_then(move || {
+        let semaphore = semaphore.clone();
+        async move {
+            match semaphore {
+                Some(semaphore) => match semaphore.try_acquire_owned() {
+                    Ok(permit) => Ok(Some(permit)),
+                    Err(_) => Err(warp::reject::custom(ServerBusy)),
+                },
+                None => Ok(None),
+            }
+        }
+    })
+}
+
+/// Whether an `Accept` header value allows a JSON response.
+/// Media ranges given a zero quality (`;q=0`) are treated as refused.
+fn accepts_json(accept: &str) -> bool {
+    accept.split(',').any(|media_range| {
+        let mut params = media_range.split(';').map(|part| part.trim());
+        let media_type = params.next().unwrap_or_default().to_ascii_lowercase();
+        let refused = params.any(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .is_some_and(|quality| quality == 0.0)
+        });
+        !refused
+            && matches!(
+                media_type.as_str(),
+                "application/json" | "application/*" | "*/*"
+            )
+    })
+}
+
+/// Filter rejecting requests whose `Accept` header excludes JSON when `ENFORCE_ACCEPT_JSON` is on.
+/// Requests without an `Accept` header accept anything.
+fn accept_json(config: Arc<Config>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("accept")
+        .and_then(move |accept: Option<String>| {
+            let enforce = config.enforce_accept_json;
+            async move {
+                match accept {
+                    Some(accept) if enforce && !accepts_json(&accept) => {
+                        Err(warp::reject::custom(NotAcceptable))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Filter passing requests only when `ADMIN_ENDPOINTS` is on; admin routes are not found otherwise
+fn admin_only(config: Arc<Config>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::any()
+        .and_then(move || {
+            let enabled = config.admin_endpoints;
+            async move {
+                if enabled {
+                    Ok(())
+                } else {
+                    Err(warp::reject::not_found())
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Filter matching requests made with the `target` method.
+/// Also matches POST requests carrying an `X-HTTP-Method-Override` header naming `target`,
+/// so clients behind proxies that block DELETE/PATCH can still reach those routes.
+/// Only methods in `METHOD_OVERRIDE_ALLOWLIST` can be reached through the override.
+fn method_or_override(target: Method) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::method()
+        .and(warp::header::optional::<String>("x-http-method-override"))
+        .and_then(move |method: Method, method_override: Option<String>| {
+            let target = target.clone();
+            async move {
+                let overridden = method == Method::POST
+                    && METHOD_OVERRIDE_ALLOWLIST.contains(&target)
+                    && method_override
+                        .is_some_and(|value| value.trim().eq_ignore_ascii_case(target.as_str()));
+                if method == target || overridden {
+                    Ok(())
+                } else {
+                    Err(warp::reject::not_found())
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// Route describing the server at the root path. GET request at /
+/// Returns the restaurant name, the server version and its uptime in seconds.
+pub fn info_route(
+    config: Arc<Config>,
+    started_at: Instant,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path::end()
+        .and(warp::get())
+        .and(with_config(config))
+        .and(warp::any().map(move || started_at))
+        .and_then(info_handler)
+}
+
+/// Route for load balancer health checks. GET request at /health
+/// Runs `SELECT 1`; returns SERVICE UNAVAILABLE with status `degraded` when it fails.
+pub fn health_route(pool: DbPool) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("health")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(health_handler)
+}
+
+/// Route to check the database is writable. GET request at /healthz/deep
+/// Writes and reads back a sentinel row in a rolled back transaction; returns SERVICE UNAVAILABLE with the reason on failure.
+pub fn deep_health_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("healthz" / "deep")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(deep_health_handler)
+}
+
+/// Route to download a SQL dump of the database. GET request at /admin/dump.sql
+/// The dump recreates the schema and data in an empty SQLite database.
+/// Only served when `ADMIN_ENDPOINTS` is on.
+pub fn dump_sql_route(
+    config: Arc<Config>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("admin" / "dump.sql")
+        .and(warp::get())
+        .and(admin_only(config.clone()))
+        .and(with_config(config))
+        .and_then(dump_sql_handler)
+}
+
+/// Route to list orders. GET request at /orders?limit=50&offset=0
+/// Pages through orders by ID; `limit` defaults to 50 and is clamped to 200, `offset` defaults to 0.
+pub fn list_all_orders_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("orders")
         .and(warp::get())
-        .and(with_db())
-        .and_then(|conn| list_order_handler(conn))
+        .and(with_db(pool))
+        .and(warp::query())
+        .and_then(list_order_handler)
+}
+
+/// Route to list the totals of every order. GET request at /orders/summary
+/// Reads the trigger-maintained `order_summary` table, so no order is recomputed.
+pub fn list_order_summaries_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / "summary")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(list_order_summaries_handler)
+}
+
+/// Route to list the largest open orders. GET request at /orders/largest?limit=5&by=quantity
+/// `by` is one of `quantity` (default), `cooking_time` or `price`, `limit` defaults to 5 and must be 1-100.
+pub fn list_largest_orders_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / "largest")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and(warp::query())
+        .and_then(list_largest_orders_handler)
+}
+
+/// Route to list all order items for a specific order. /orders/{order_id}/items
+/// Returns NOT FOUND if the order does not exist.
+pub fn list_order_items_for_order_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / i64 / "items")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(|order_id, conn| list_order_items_for_order_handler(conn, order_id))
+}
+
+/// Route to get a single order. /orders/{order_id}
+/// Returns the order with its items and totals, or NOT FOUND if the order does not exist.
+pub fn get_order_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / i64)
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(|order_id, conn| get_order_handler(conn, order_id))
+}
+
+/// Route to cancel an order. DELETE request at /orders/{order_id}
+/// Deletes the order with all of its items, or answers NOT FOUND if the order does not exist.
+pub fn delete_order_route(
+    pool: DbPool,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / i64)
+        .and(method_or_override(Method::DELETE))
+        .and(with_db(pool))
+        .and(with_order_events(order_events))
+        .and_then(|order_id, conn, order_events| delete_order_handler(conn, order_events, order_id))
+}
+
+/// Route to get a specific menu item from an order. /orders/{order_id}/items/{menu_id}
+/// The order-scoped counterpart of /tables/{table_id}/items/{menu_id}; returns NOT FOUND if the
+/// order doesn't contain the menu.
+pub fn get_item_from_order_by_id_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / i64 / "items" / i64)
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(|order_id, menu_id, conn| {
+            get_order_item_for_order_handler(conn, order_id, menu_id)
+        })
 }
 
 /// Route to create a new order.
-/// POST request that expects `table_id` as an i64 and `menu_ids` as a Vec<i64>.
-/// Returns BAD REQUEST if `menu_ids` is empty.
-/// If there's an existing active order for the given `table_id`, it adds new items to it.
-/// Otherwise, creates a new order and returns the order ID.
-pub fn create_order_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+/// POST request that expects `table_id` as an i64 and `menu_ids` as a Vec<i64>,
+/// `items` as a list of `{menu_id, quantity}`, or both.
+/// Returns BAD REQUEST if no items are given or a quantity is below 1,
+/// and PAYLOAD TOO LARGE for bodies over 16KB.
+/// If there's an existing active order for the given `table_id`, it adds new items to it
+/// and returns 200 with the whole updated order.
+/// Otherwise, creates a new order and returns 201 with the new order and a `Location` header at /orders/{order_id},
+/// replacing the table's cancelled order if it has one.
+/// An optional `Idempotency-Key` header makes retries safe: a repeated key gets the original
+/// response back without adding the items again, and UNPROCESSABLE ENTITY if it came with a different body.
+pub fn create_order_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("orders" / "create")
         .and(warp::post())
-        .and(with_db())
-        .and(warp::body::json())
-        .and_then(|conn, req_body| create_order_handler(conn, req_body))
+        .and(warp::body::content_length_limit(MAX_CREATE_BODY_BYTES))
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(with_table_locks(table_locks))
+        .and(with_order_events(order_events))
+        .and(warp::header::optional::<String>("idempotency-key"))
+        .and(json_body(config))
+        .and_then(create_order_handler)
+}
+
+/// Route to validate an order without creating it. POST request at /orders/validate
+/// Expects the same body as order creation and returns `valid`, the normalized `items` and any `errors`.
+pub fn validate_order_route(
+    config: Arc<Config>,
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / "validate")
+        .and(warp::post())
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(json_body(config))
+        .and_then(validate_order_handler)
+}
+
+/// Route to copy an order's items to another table. POST request at /orders/{order_id}/copy-to
+/// Expects `table_id` as an i64 and creates a new order there with the same items and quantities.
+/// Returns NOT FOUND for a missing order or table and CONFLICT if the table already has an order.
+pub fn copy_order_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / i64 / "copy-to")
+        .and(warp::post())
+        .and(with_db(pool))
+        .and(with_table_locks(table_locks))
+        .and(with_order_events(order_events))
+        .and(json_body(config))
+        .and_then(|order_id, conn, table_locks, order_events, req_body| {
+            copy_order_handler(conn, table_locks, order_events, order_id, req_body)
+        })
+}
+
+/// Route to move an order through its lifecycle. PATCH request at /orders/{order_id}/status
+/// Expects `status`, one of pending, cooking, served or cancelled; anything else is a BAD REQUEST.
+pub fn update_order_status_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("orders" / i64 / "status")
+        .and(method_or_override(Method::PATCH))
+        .and(with_db(pool))
+        .and(with_order_events(order_events))
+        .and(json_body(config))
+        .and_then(|order_id, conn, order_events, req_body| {
+            update_order_status_handler(conn, order_events, order_id, req_body)
+        })
 }
 
 /// Route to delete a specific menu item from a table.
 /// DELETE request at /orders/{table_id}/items/{item_id}.
 /// Deletes the item and returns a success/error message.
 /// If the deleted item was the last one, updates the order status to complete.
-pub fn delete_item_from_order_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone
-{
+/// Deleting an item that is already gone is a no-op that still succeeds.
+/// Also reachable as a POST with `X-HTTP-Method-Override: DELETE`.
+pub fn delete_item_from_order_route(
+    pool: DbPool,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("orders" / i64 / "items" / i64)
-        .and(warp::delete())
-        .and(with_db())
-        .and_then(|table_id, menu_id, conn| delete_order_item_handler(conn, table_id, menu_id))
+        .and(method_or_override(Method::DELETE))
+        .and(with_db(pool))
+        .and(with_table_locks(table_locks))
+        .and(with_order_events(order_events))
+        .and_then(|table_id, menu_id, conn, table_locks, order_events| {
+            delete_order_item_handler(conn, table_locks, order_events, table_id, menu_id)
+        })
+}
+
+/// Route to follow order changes live. Websocket at /ws/orders
+/// Sends the current order list on connect, then `{event, order_id}` whenever an order is
+/// created, updated or deleted, with `event` one of created, updated or deleted.
+pub fn ws_orders_route(
+    pool: DbPool,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("ws" / "orders")
+        .and(warp::ws())
+        .and(with_db(pool))
+        .and(with_order_events(order_events))
+        .and_then(ws_orders_handler)
 }
 
 /// Route to list all tables
-pub fn list_tables_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+pub fn list_tables_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("tables")
         .and(warp::get())
-        .and(with_db())
-        .and_then(|conn| list_table_handler(conn))
+        .and(with_db(pool))
+        .and_then(list_table_handler)
+}
+
+/// Route to get a single table. /tables/{table_id}
+/// Returns the table's code, or NOT FOUND if the table does not exist; `/tables/{table_id}/items` is a separate route.
+pub fn get_table_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / i64)
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(|table_id, conn| get_table_handler(conn, table_id))
+}
+
+/// Route to get a table's receipt. GET request at /tables/{table_id}/receipt
+/// Returns the order's line items at current menu prices with `subtotal`, `tax` at `TAX_RATE`
+/// and `total` in cents, or NOT FOUND if the table has no order or it was cancelled.
+pub fn order_receipt_route(
+    config: Arc<Config>,
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / i64 / "receipt")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and(with_config(config))
+        .and_then(|table_id, conn, config| order_receipt_handler(conn, config, table_id))
+}
+
+/// Route to list tables by the load of their open order. GET request at /tables/by-load?direction=asc
+/// `direction` is `asc` (default, least loaded first) or `desc`; tables without an order have load 0.
+pub fn list_tables_by_load_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / "by-load")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and(warp::query())
+        .and_then(list_tables_by_load_handler)
+}
+
+/// Route to list the tables that currently have an order. GET request at /tables/occupied
+/// Each table comes with its `table_id`, `code` and the order's total cooking time, sorted by code.
+pub fn occupied_tables_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / "occupied")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(occupied_tables_handler)
+}
+
+/// Route to get the kitchen's load. GET request at /kitchen/load
+/// Lists each table with an order still to be cooked and its total cooking time, most loaded first.
+pub fn kitchen_load_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("kitchen" / "load")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(kitchen_load_handler)
 }
 
 /// Route to create a table.
-/// POST request that expects a `code` in the request body and returns the table's ID upon creation.
-pub fn create_table_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+/// POST request that expects a `code` in the request body of at most 16KB and returns the table's ID upon creation.
+pub fn create_table_route(
+    config: Arc<Config>,
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("tables" / "create")
         .and(warp::post())
-        .and(with_db())
-        .and(warp::body::json())
-        .and_then(|conn, req_body| create_table_handler(conn, req_body))
+        .and(warp::body::content_length_limit(MAX_CREATE_BODY_BYTES))
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(json_body(config))
+        .and_then(create_table_handler)
+}
+
+/// Route to create several tables at once. POST request at /tables/bulk
+/// Expects a list of `{code}` and creates them in a single transaction, skipping codes that exist.
+/// Returns each code's `{code, id, created}`, and CONFLICT if the tables would exceed MAX_TABLES.
+pub fn create_tables_bulk_route(
+    config: Arc<Config>,
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / "bulk")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(MAX_CREATE_BODY_BYTES))
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(json_body(config))
+        .and_then(create_tables_bulk_handler)
+}
+
+/// Route to change a table's code. PUT request at /tables/{table_id}
+/// Expects `code`; answers NOT FOUND for a missing table and CONFLICT when another table has the code.
+pub fn update_table_route(
+    config: Arc<Config>,
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / i64)
+        .and(warp::put())
+        .and(with_db(pool))
+        .and(json_body(config))
+        .and_then(|table_id, conn, data| update_table_handler(conn, table_id, data))
+}
+
+/// Route to delete a table. DELETE request at /tables/{table_id}
+/// Tables with an active order are kept and answer 409.
+pub fn delete_table_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / i64)
+        .and(method_or_override(Method::DELETE))
+        .and(with_db(pool))
+        .and_then(|table_id, conn| delete_table_handler(conn, table_id))
 }
 
 /// Route to list all order items for a specific table. /tables/{table_id}/items
+/// Returns NOT FOUND if the table does not exist.
 pub fn list_order_items_for_table_route(
+    pool: DbPool,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("tables" / i64 / "items")
         .and(warp::get())
-        .and(with_db())
+        .and(with_db(pool))
         .and_then(|table_id, conn| list_order_items_for_table_handler(conn, table_id))
 }
 
+/// Route to set the quantity of an item in a table's order. PATCH request at /tables/{table_id}/items/{menu_id}
+/// Expects `quantity`; 0 deletes the item, and the order when it was the last one.
+/// Answers NOT FOUND when the table's order has no such item.
+pub fn set_item_quantity_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / i64 / "items" / i64)
+        .and(method_or_override(Method::PATCH))
+        .and(with_db(pool))
+        .and(with_table_locks(table_locks))
+        .and(with_order_events(order_events))
+        .and(json_body(config))
+        .and_then(
+            |table_id, menu_id, conn, table_locks, order_events, req_body| {
+                set_item_quantity_handler(
+                    conn,
+                    table_locks,
+                    order_events,
+                    table_id,
+                    menu_id,
+                    req_body,
+                )
+            },
+        )
+}
+
+/// Route to mark an item of a table's order as delivered. POST request at /tables/{table_id}/items/{menu_id}/serve
+/// Sets the item's `served` flag; answers NOT FOUND when the table's order has no such item.
+pub fn mark_item_served_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / i64 / "items" / i64 / "serve")
+        .and(warp::post())
+        .and(with_db(pool))
+        .and_then(|table_id, menu_id, conn| mark_item_served_handler(conn, table_id, menu_id))
+}
+
+/// Route to clear a table's order. DELETE request at /tables/{table_id}/items
+/// Deletes every item and then the order; answers NOT FOUND when the table has no active order.
+/// Also reachable as a POST with `X-HTTP-Method-Override: DELETE`.
+pub fn clear_order_route(
+    pool: DbPool,
+    table_locks: Arc<TableLocks>,
+    order_events: Arc<OrderEvents>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("tables" / i64 / "items")
+        .and(method_or_override(Method::DELETE))
+        .and(with_db(pool))
+        .and(with_table_locks(table_locks))
+        .and(with_order_events(order_events))
+        .and_then(|table_id, conn, table_locks, order_events| {
+            clear_order_handler(conn, table_locks, order_events, table_id)
+        })
+}
+
 /// Route to get a specific menu item from a table. /tables/{table_id}/items/{item_id}
-pub fn get_item_from_order_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+pub fn get_item_from_order_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("tables" / i64 / "items" / i64)
         .and(warp::get())
-        .and(with_db())
+        .and(with_db(pool))
         .and_then(|table_id, menu_id, conn| {
             get_order_item_for_table_handler(conn, table_id, menu_id)
         })
 }
 
-/// Route to list all menus
-pub fn list_menus_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+/// Route to list the order items of several tables in one call. GET request at /items/by-tables?ids=1,2,3
+/// Returns a map from table ID to its items, with an empty list for tables without items.
+/// At most 50 distinct table IDs can be requested at once.
+pub fn list_order_items_for_tables_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("items" / "by-tables")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and(warp::query())
+        .and_then(list_order_items_for_tables_handler)
+}
+
+/// Route to list all menus. GET request at /menus
+/// An optional `?category=` query lists only the menus in that category.
+pub fn list_menus_route(
+    pool: DbPool,
+    menu_cache: Arc<MenuCache>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("menus")
         .and(warp::get())
-        .and(with_db())
-        .and_then(|conn| list_menu_handler(conn))
+        .and(with_db(pool))
+        .and(with_menu_cache(menu_cache))
+        .and(warp::query())
+        .and_then(list_menu_handler)
+}
+
+/// Route to search menus by name. GET request at /menus/search?q=...
+/// Lists the menus whose name contains `q`, ignoring case; an empty `q` is a BAD REQUEST.
+pub fn search_menus_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / "search")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and(warp::query())
+        .and_then(search_menus_handler)
 }
 
 /// Route to create a menu.
-/// POST request that expects a `name` in the request body.
-pub fn create_menu_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+/// POST request that expects a `name`, and optionally a `price`, `category` and `cooking_time`, in a body of at most 16KB.
+pub fn create_menu_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    menu_cache: Arc<MenuCache>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     warp::path!("menus" / "create")
         .and(warp::post())
-        .and(with_db())
-        .and(warp::body::json())
-        .and_then(|conn, req_body| create_menu_handler(conn, req_body))
+        .and(warp::body::content_length_limit(MAX_CREATE_BODY_BYTES))
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(with_menu_cache(menu_cache))
+        .and(json_body(config))
+        .and_then(create_menu_handler)
+}
+
+/// Route to get a single menu. /menus/{menu_id}
+/// Returns the menu's name, price and category, or NOT FOUND if the menu does not exist.
+pub fn get_menu_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / i64)
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(|menu_id, conn| get_menu_handler(conn, menu_id))
+}
+
+/// Route to rename a menu. PUT request at /menus/{menu_id}
+/// Expects `name`; answers NOT FOUND for a missing menu and CONFLICT when another menu has the name.
+pub fn update_menu_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    menu_cache: Arc<MenuCache>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / i64)
+        .and(warp::put())
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(with_menu_cache(menu_cache))
+        .and(json_body(config))
+        .and_then(|menu_id, conn, config, menu_cache, data| {
+            update_menu_handler(conn, config, menu_cache, menu_id, data)
+        })
 }
 
-/// Route to get state of restaurant.
-// pub fn restaurant_state_route() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
-//     warp::path!("state")
-//         .and(warp::get())
-//         .and(with_db())
-//         .and_then(|conn| get_state_handler(conn))
-// }
+/// Route to delete a menu. DELETE request at /menus/{menu_id}
+/// Menus still used by an order item are kept and answer CONFLICT.
+pub fn delete_menu_route(
+    pool: DbPool,
+    menu_cache: Arc<MenuCache>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / i64)
+        .and(method_or_override(Method::DELETE))
+        .and(with_db(pool))
+        .and(with_menu_cache(menu_cache))
+        .and_then(|menu_id, conn, menu_cache| delete_menu_handler(conn, menu_cache, menu_id))
+}
+
+/// Route to rank menus by how often they're ordered. GET request at /menus/popularity
+/// Returns each ordered menu's `total_quantity` across all current orders, most ordered first.
+pub fn menu_popularity_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / "popularity")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(menu_popularity_handler)
+}
+
+/// Route to get order statistics for a menu. /menus/{menu_id}/stats
+/// Returns zeros for a menu that has never been ordered and NOT FOUND for a missing menu.
+pub fn get_menu_stats_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / i64 / "stats")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(|menu_id, conn| get_menu_stats_handler(conn, menu_id))
+}
+
+/// Route to calibrate a menu's cooking time. PATCH request at /menus/{id}/cooking-time
+/// Expects `cooking_time`, which applies to order items created afterwards; existing items keep theirs.
+pub fn set_menu_cooking_time_route(
+    config: Arc<Config>,
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / i64 / "cooking-time")
+        .and(method_or_override(Method::PATCH))
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(json_body(config))
+        .and_then(|menu_id, conn, config, req_body| {
+            set_menu_cooking_time_handler(conn, config, menu_id, req_body)
+        })
+}
+
+/// Route to ensure a set of menus exist.
+/// POST request that expects `names` as a Vec<String> and returns a map of each name to its menu ID.
+/// Existing menus keep their ID, missing ones are created in a single transaction.
+pub fn ensure_menus_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    menu_cache: Arc<MenuCache>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / "ensure")
+        .and(warp::post())
+        .and(with_db(pool))
+        .and(with_config(config.clone()))
+        .and(with_menu_cache(menu_cache))
+        .and(json_body(config))
+        .and_then(ensure_menus_handler)
+}
+
+/// Route to set the display order of all menus.
+/// POST request that expects `menu_ids` as a Vec<i64> listing every menu exactly once.
+/// Menus are listed in that order afterwards; returns BAD REQUEST if any menu is missing or unknown.
+pub fn reorder_menus_route(
+    config: Arc<Config>,
+    pool: DbPool,
+    menu_cache: Arc<MenuCache>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("menus" / "reorder")
+        .and(warp::post())
+        .and(with_db(pool))
+        .and(with_menu_cache(menu_cache))
+        .and(json_body(config))
+        .and_then(reorder_menus_handler)
+}
+
+/// Route to get state of restaurant. /state
+/// Returns every table, menu and order in one response.
+pub fn restaurant_state_route(
+    pool: DbPool,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    warp::path!("state")
+        .and(warp::get())
+        .and(with_db(pool))
+        .and_then(get_state_handler)
+}
 
 /// Combine all routes
-pub fn restaurant_routes() -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
-    let routes = create_order_route()
-        .or(create_table_route())
-        .or(create_menu_route())
-        .or(list_tables_route())
-        .or(list_menus_route())
-        .or(list_all_orders_route())
-        .or(delete_item_from_order_route())
-        .or(list_order_items_for_table_route())
-        .or(get_item_from_order_route());
-
-    routes.recover(handle_rejection)
+pub fn restaurant_routes(
+    config: Arc<Config>,
+    started_at: Instant,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    // Shared by the menu routes so that writes invalidate what reads cache
+    let menu_cache = Arc::new(MenuCache::new(config.menu_cache_ttl()));
+    // Shared by the order routes so that changes to one table are serialized
+    let table_locks = Arc::new(TableLocks::default());
+    // Shared by the order routes, which publish into it, and the websocket route, which follows it
+    let order_events = Arc::new(OrderEvents::default());
+    // Shared by every route that reads or writes the database
+    let pool = init_pool(&config.db_path, config.db_busy_timeout());
+
+    let routes = info_route(config.clone(), started_at)
+        .or(health_route(pool.clone()))
+        .or(deep_health_route(pool.clone()))
+        .or(dump_sql_route(config.clone()))
+        .or(create_order_route(
+            config.clone(),
+            pool.clone(),
+            table_locks.clone(),
+            order_events.clone(),
+        ))
+        .or(validate_order_route(config.clone(), pool.clone()))
+        .or(copy_order_route(
+            config.clone(),
+            pool.clone(),
+            table_locks.clone(),
+            order_events.clone(),
+        ))
+        .or(update_order_status_route(
+            config.clone(),
+            pool.clone(),
+            order_events.clone(),
+        ))
+        .or(create_table_route(config.clone(), pool.clone()))
+        .or(create_tables_bulk_route(config.clone(), pool.clone()))
+        .or(update_table_route(config.clone(), pool.clone()))
+        .or(delete_table_route(pool.clone()))
+        .or(create_menu_route(
+            config.clone(),
+            pool.clone(),
+            menu_cache.clone(),
+        ))
+        .or(update_menu_route(
+            config.clone(),
+            pool.clone(),
+            menu_cache.clone(),
+        ))
+        .or(delete_menu_route(pool.clone(), menu_cache.clone()))
+        .or(ensure_menus_route(
+            config.clone(),
+            pool.clone(),
+            menu_cache.clone(),
+        ))
+        .or(reorder_menus_route(
+            config.clone(),
+            pool.clone(),
+            menu_cache.clone(),
+        ))
+        .or(restaurant_state_route(pool.clone()))
+        .or(list_tables_route(pool.clone()))
+        .or(list_tables_by_load_route(pool.clone()))
+        .or(occupied_tables_route(pool.clone()))
+        .or(get_table_route(pool.clone()))
+        .or(order_receipt_route(config.clone(), pool.clone()))
+        .or(kitchen_load_route(pool.clone()))
+        .or(list_menus_route(pool.clone(), menu_cache.clone()))
+        .or(search_menus_route(pool.clone()))
+        .or(menu_popularity_route(pool.clone()))
+        .or(get_menu_route(pool.clone()))
+        .or(get_menu_stats_route(pool.clone()))
+        .or(set_menu_cooking_time_route(config.clone(), pool.clone()))
+        .or(list_all_orders_route(pool.clone()))
+        .or(list_largest_orders_route(pool.clone()))
+        .or(list_order_summaries_route(pool.clone()))
+        .or(get_order_route(pool.clone()))
+        .or(delete_order_route(pool.clone(), order_events.clone()))
+        .or(list_order_items_for_order_route(pool.clone()))
+        .or(get_item_from_order_by_id_route(pool.clone()))
+        .or(delete_item_from_order_route(
+            pool.clone(),
+            table_locks.clone(),
+            order_events.clone(),
+        ))
+        .or(clear_order_route(
+            pool.clone(),
+            table_locks.clone(),
+            order_events.clone(),
+        ))
+        .or(set_item_quantity_route(
+            config.clone(),
+            pool.clone(),
+            table_locks,
+            order_events.clone(),
+        ))
+        .or(ws_orders_route(pool.clone(), order_events))
+        .or(mark_item_served_route(pool.clone()))
+        .or(list_order_items_for_table_route(pool.clone()))
+        .or(list_order_items_for_tables_route(pool.clone()))
+        .or(get_item_from_order_route(pool.clone()));
+
+    // Every route is also served under /v1 and, with camelCase fields, under /v2
+    let versioned_routes = api_version()
+        .and(warp::path::full())
+        .and(route_enabled(config.clone()))
+        .and(routes)
+        .and_then(rename_response_fields);
+
+    // Log the request line with allowlisted headers when body logging is on
+    let log_bodies = config.log_bodies;
+    let log_request = warp::log::custom(move |info| {
+        if log_bodies {
+            let headers: Vec<_> = LOGGED_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    let value = info.request_headers().get(*name)?.to_str().ok()?;
+                    Some(format!("{}: {}", name, value))
+                })
+                .collect();
+            tracing::info!(method = %info.method(), path = info.path(), headers = ?headers, "request");
+        }
+    });
+
+    // Shared by every request; a permit is held until the route has produced its reply
+    let request_semaphore = (config.max_concurrent_requests > 0)
+        .then(|| Arc::new(Semaphore::new(config.max_concurrent_requests)));
+
+    // The concurrency, chaos and Accept filters run ahead of every route and are no-ops unless configured
+    limit_concurrency(request_semaphore)
+        .and(chaos(config.clone()))
+        .and(accept_json(config.clone()))
+        .and(versioned_routes)
+        .map(|_permit, reply| reply)
+        .recover(handle_rejection)
+        .and(with_config(config.clone()))
+        .and_then(pretty_error_body)
+        .and(with_config(config.clone()))
+        .and_then(log_response_body)
+        .with(cors(&config))
+        .with(log_request)
+}
+
+/// CORS policy letting browsers on the `CORS_ALLOWED_ORIGINS` origins call every route.
+/// Preflight requests are answered here and never reach a route.
+fn cors(config: &Config) -> warp::cors::Builder {
+    let cors = warp::cors()
+        .allow_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
+        .allow_headers(vec![
+            "content-type",
+            "idempotency-key",
+            "x-http-method-override",
+            "x-request-timeout-ms",
+        ]);
+    if config
+        .cors_allowed_origins
+        .iter()
+        .any(|origin| origin == "*")
+    {
+        cors.allow_any_origin()
+    } else {
+        cors.allow_origins(config.cors_allowed_origins.iter().map(String::as_str))
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tests::remove_db_files;
+    use crate::db::{get_db_conn, initialize_db};
+    use crate::handlers::tests::{convert_response_to_json, setup_static_data, setup_test_db};
+    use crate::models::{get_current_state, OrderSummary};
+    use rusqlite::Connection;
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    // Initialize a fresh database file in the temp directory and a config pointing at it
+    fn fresh_db_config(name: &str) -> (Arc<Config>, PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "restaurant-test-{}-{}.db",
+            std::process::id(),
+            name
+        ));
+        remove_db_files(&path);
+        let db_path = path.to_string_lossy().to_string();
+        initialize_db(&db_path, Config::default().db_busy_timeout());
+        (
+            Arc::new(Config {
+                db_path,
+                ..Config::default()
+            }),
+            path,
+        )
+    }
+
+    // Build the item deletion route on top of a single test connection
+    fn delete_item_route_with_conn(
+        conn: DbConn,
+    ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+        let conn = Arc::new(Mutex::new(Some(conn)));
+        warp::path!("orders" / i64 / "items" / i64)
+            .and(method_or_override(Method::DELETE))
+            .and(warp::any().map(move || {
+                conn.lock()
+                    .unwrap()
+                    .take()
+                    .expect("Test connection already used")
+            }))
+            .and_then(|table_id, menu_id, conn| {
+                delete_order_item_handler(conn, Arc::default(), Arc::default(), table_id, menu_id)
+            })
+    }
+
+    // Seed an order for table 1 containing menus 1 and 2
+    fn setup_order(conn: &Connection) {
+        conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [1])
+            .expect("Order creation failed");
+        let order_id = conn.last_insert_rowid();
+        for (menu_id, cooking_time) in [(1, 6), (2, 7)] {
+            conn.execute(
+                "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (?1, ?2, ?3)",
+                [order_id, menu_id, cooking_time],
+            )
+            .expect("OrderItems creation failed");
+        }
+    }
+
+    // Test Case: POST with a DELETE method override removes the item
+    #[tokio::test]
+    async fn test_method_override_deletes_item() {
+        let conn = setup_test_db();
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/1/items/2")
+            .header("X-HTTP-Method-Override", "DELETE")
+            .reply(&delete_item_route_with_conn(conn))
+            .await;
+        // Expecting the override to reach the delete handler and remove menu 2
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data = convert_response_to_json(resp.map(Into::into)).await;
+        assert_eq!(
+            json_data["success"].as_str(),
+            Some("Menu deleted successfully")
+        );
+    }
+
+    // Test Case: POST without an override or with a non-matching override does not match the delete route
+    #[tokio::test]
+    async fn test_method_override_requires_matching_header() {
+        for method_override in [None, Some("GET"), Some("PATCH")] {
+            let conn = setup_test_db();
+            setup_static_data(&conn);
+            setup_order(&conn);
+            let mut request = warp::test::request()
+                .method("POST")
+                .path("/orders/1/items/2");
+            if let Some(value) = method_override {
+                request = request.header("X-HTTP-Method-Override", value);
+            }
+            let matched = request.matches(&delete_item_route_with_conn(conn)).await;
+            assert!(!matched, "override {:?} should not match", method_override);
+        }
+    }
+
+    // Test Case: A database that can't be opened turns requests away with a 503 instead of a panic
+    #[tokio::test]
+    async fn test_database_unavailable() {
+        let db_path = std::env::temp_dir()
+            .join(format!(
+                "restaurant-test-{}-missing-dir",
+                std::process::id()
+            ))
+            .join("restaurant.db");
+        assert!(get_db_conn(&db_path.to_string_lossy(), Duration::ZERO).is_err());
+        // A short checkout timeout keeps the test fast; the server uses the pool's default
+        let pool = r2d2::Pool::builder()
+            .connection_timeout(Duration::from_millis(200))
+            .build_unchecked(r2d2_sqlite::SqliteConnectionManager::file(&db_path));
+        let filter = with_db(pool)
+            .map(|_conn: DbConn| "connected")
+            .recover(handle_rejection);
+
+        let resp = warp::test::request().path("/").reply(&filter).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(
+            json_data,
+            serde_json::json!({"error": "Database unavailable"})
+        );
+    }
+
+    // Test Case: The chaos filter is inert when not configured
+    #[tokio::test]
+    async fn test_chaos_filter_inert_by_default() {
+        let filter = chaos(Arc::new(Config::default())).map(|| "ok");
+        for _ in 0..50 {
+            let started = std::time::Instant::now();
+            let resp = warp::test::request().path("/").reply(&filter).await;
+            assert_eq!(resp.status(), warp::http::StatusCode::OK);
+            assert!(started.elapsed() < Duration::from_millis(100));
+        }
+    }
+
+    // Test Case: The chaos filter fails every request with an error rate of 1
+    #[tokio::test]
+    async fn test_chaos_filter_injects_errors() {
+        let config = Config {
+            chaos_error_rate: 1.0,
+            ..Config::default()
+        };
+        let filter = chaos(Arc::new(config))
+            .map(|| "ok")
+            .recover(handle_rejection);
+        let resp = warp::test::request().path("/").reply(&filter).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    // Test Case: Every GET endpoint works against a freshly-initialized empty database
+    #[tokio::test]
+    async fn test_get_endpoints_on_empty_database() {
+        let (config, path) = fresh_db_config("empty");
+        let routes = restaurant_routes(config.clone(), Instant::now());
+
+        // Listing endpoints return 200 with an empty array
+        for endpoint in ["/tables", "/menus", "/orders", "/orders/largest"] {
+            let resp = warp::test::request().path(endpoint).reply(&routes).await;
+            assert_eq!(resp.status(), warp::http::StatusCode::OK, "{}", endpoint);
+            let json_data: serde_json::Value =
+                serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+            assert_eq!(json_data, serde_json::json!([]), "{}", endpoint);
+        }
+
+        // The info endpoint responds as well
+        let resp = warp::test::request().path("/").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+
+        // Lookups of specific records report them missing rather than failing
+        for endpoint in [
+            "/orders/1/items",
+            "/tables/1/items",
+            "/tables/1/items/1",
+            "/menus/1",
+            "/tables/1",
+        ] {
+            let resp = warp::test::request().path(endpoint).reply(&routes).await;
+            assert_eq!(
+                resp.status(),
+                warp::http::StatusCode::NOT_FOUND,
+                "{}",
+                endpoint
+            );
+        }
+
+        // The restaurant state is empty as well
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        let state = get_current_state(&conn).expect("Failed to load state");
+        assert!(state.tables.is_empty() && state.menus.is_empty() && state.orders.is_empty());
+
+        // An order that has no items yet reports a zero total instead of failing
+        conn.execute("INSERT INTO tables (code) VALUES ('T-01')", [])
+            .expect("Failed to insert table data");
+        conn.execute("INSERT INTO orders (table_id) VALUES (1)", [])
+            .expect("Order creation failed");
+        let resp = warp::test::request().path("/orders").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data[0]["total_cooking_time"].as_i64(), Some(0));
+        assert_eq!(json_data[0]["menus"], serde_json::json!([]));
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Logged bodies are truncated to the maximum length
+    #[test]
+    fn test_truncate_body() {
+        assert_eq!(truncate_body(b"{\"code\":\"T-01\"}"), "{\"code\":\"T-01\"}");
+        let long_body = vec![b'a'; MAX_LOGGED_BODY_BYTES + 10];
+        let logged = truncate_body(&long_body);
+        assert!(logged.starts_with(&"a".repeat(MAX_LOGGED_BODY_BYTES)));
+        assert!(logged.ends_with(&format!("({} bytes total)", MAX_LOGGED_BODY_BYTES + 10)));
+    }
+
+    // Test Case: Request and response bodies reach their destination intact with body logging on
+    #[tokio::test]
+    async fn test_body_logging_preserves_bodies() {
+        let config = Arc::new(Config {
+            log_bodies: true,
+            ..Config::default()
+        });
+        let filter = json_body::<serde_json::Value>(config.clone())
+            .map(|body: serde_json::Value| warp::reply::json(&body))
+            .recover(handle_rejection)
+            .and(with_config(config))
+            .and_then(log_response_body);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .body(r#"{"code":"T-01"}"#)
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(resp.body().as_ref(), br#"{"code":"T-01"}"#);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .body("not json")
+            .reply(&filter)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+    }
+
+    // Test Case: A disabled route and the paths below it answer 404 in every version without reaching a handler
+    #[tokio::test]
+    async fn test_disabled_routes() {
+        let (config, path) = fresh_db_config("disabled-routes");
+        let config = Arc::new(Config {
+            disabled_routes: vec!["/tables".to_string()],
+            ..(*config).clone()
+        });
+        let routes = restaurant_routes(config.clone(), Instant::now());
+
+        for (method, disabled_path) in [
+            ("GET", "/tables"),
+            ("GET", "/v2/tables/by-load"),
+            ("POST", "/v1/tables/create"),
+        ] {
+            let resp = warp::test::request()
+                .method(method)
+                .path(disabled_path)
+                .json(&serde_json::json!({"code": "T-01"}))
+                .reply(&routes)
+                .await;
+            assert_eq!(
+                resp.status(),
+                warp::http::StatusCode::NOT_FOUND,
+                "{}",
+                disabled_path
+            );
+        }
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        assert_eq!(count_rows(&conn, "tables"), 0);
+
+        // Other routes are still served
+        let resp = warp::test::request().path("/menus").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Creating a menu invalidates the cached menu list
+    #[tokio::test]
+    async fn test_menu_create_invalidates_cache() {
+        let (config, path) = fresh_db_config("menu-cache");
+        let config = Arc::new(Config {
+            menu_cache_ttl_secs: 60,
+            ..(*config).clone()
+        });
+        let routes = restaurant_routes(config, Instant::now());
+
+        // Prime the cache with the empty menu list
+        let resp = warp::test::request().path("/menus").reply(&routes).await;
+        assert_eq!(resp.body().as_ref(), b"[]");
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/menus/create")
+            .json(&serde_json::json!({"name": "M-01"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+
+        // The next read must reflect the new menu instead of the cached empty list
+        let resp = warp::test::request().path("/menus").reply(&routes).await;
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data[0]["name"].as_str(), Some("M-01"));
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Concurrent orders and deletions on one table end up with consistent quantities
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_order_changes_on_one_table() {
+        let (config, path) = fresh_db_config("table-locks");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+
+        // Race ten orders of menu 1 for table 1 plus three for table 2
+        let mut tasks = Vec::new();
+        for table_id in [1; 10].into_iter().chain([2; 3]) {
+            let routes = routes.clone();
+            tasks.push(tokio::spawn(async move {
+                warp::test::request()
+                    .method("POST")
+                    .path("/orders/create")
+                    .json(&serde_json::json!({"table_id": table_id, "menu_ids": [1]}))
+                    .reply(&routes)
+                    .await
+                    .status()
+            }));
+        }
+        for task in tasks {
+            assert!(task.await.unwrap().is_success());
+        }
+
+        // Then race four deletions of menu 1 from table 1
+        let mut tasks = Vec::new();
+        for _ in 0..4 {
+            let routes = routes.clone();
+            tasks.push(tokio::spawn(async move {
+                warp::test::request()
+                    .method("DELETE")
+                    .path("/orders/1/items/1")
+                    .reply(&routes)
+                    .await
+                    .status()
+            }));
+        }
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), warp::http::StatusCode::OK);
+        }
+
+        // Each table has a single order whose quantity reflects every request
+        let quantities: Vec<(i64, i64)> = conn
+            .prepare(
+                "SELECT orders.table_id, order_items.quantity FROM order_items
+                JOIN orders ON order_items.order_id = orders.id
+                ORDER BY orders.table_id",
+            )
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(quantities, vec![(1, 6), (2, 3)]);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: More concurrent order creations than pooled connections all succeed without busy errors
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_order_creation_with_pool() {
+        let (config, path) = fresh_db_config("pool-orders");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+
+        // Thirty orders of two items spread over the three tables
+        let mut tasks = Vec::new();
+        for i in 0..30 {
+            let routes = routes.clone();
+            let table_id = i % 3 + 1;
+            tasks.push(tokio::spawn(async move {
+                warp::test::request()
+                    .method("POST")
+                    .path("/orders/create")
+                    .json(&serde_json::json!({"table_id": table_id, "menu_ids": [1, 2]}))
+                    .reply(&routes)
+                    .await
+            }));
+        }
+        for task in tasks {
+            let resp = task.await.unwrap();
+            assert_ne!(resp.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+            assert!(
+                resp.status().is_success(),
+                "{:?}",
+                String::from_utf8_lossy(resp.body())
+            );
+        }
+
+        // Every unit of every request was recorded
+        let quantity: i64 = conn
+            .query_row("SELECT SUM(quantity) FROM order_items", [], |row| {
+                row.get(0)
+            })
+            .expect("Failed to sum quantities");
+        assert_eq!(quantity, 60);
+
+        remove_db_files(&path);
+    }
+
+    // Count the rows of a table in the test database
+    fn count_rows(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get(0)
+        })
+        .expect("Failed to count rows")
+    }
+
+    // Test Case: A failure part way through creating an order leaves nothing behind
+    #[tokio::test]
+    async fn test_create_order_rolls_back_on_error() {
+        let (config, path) = fresh_db_config("create-order-rollback");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        // Force the second item of the order to fail after the order and first item are written
+        conn.execute(
+            "CREATE TRIGGER fail_menu_2 BEFORE INSERT ON order_items WHEN NEW.menu_id = 2
+            BEGIN SELECT RAISE(ABORT, 'forced failure'); END",
+            [],
+        )
+        .expect("Failed to create trigger");
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({"table_id": 1, "menu_ids": [1, 2]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(count_rows(&conn, "orders"), 0);
+        assert_eq!(count_rows(&conn, "order_items"), 0);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: A failure part way through deleting an item leaves the order untouched
+    #[tokio::test]
+    async fn test_delete_order_item_rolls_back_on_error() {
+        let (config, path) = fresh_db_config("delete-item-rollback");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (1)", [])
+            .expect("Order creation failed");
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (1, 1, 6)",
+            [],
+        )
+        .expect("OrderItems creation failed");
+        // Force the emptied order's deletion to fail after its last item is deleted
+        conn.execute(
+            "CREATE TRIGGER fail_order_delete BEFORE DELETE ON orders
+            BEGIN SELECT RAISE(ABORT, 'forced failure'); END",
+            [],
+        )
+        .expect("Failed to create trigger");
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("DELETE")
+            .path("/orders/1/items/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(count_rows(&conn, "orders"), 1);
+        assert_eq!(count_rows(&conn, "order_items"), 1);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Reordering menus changes the order they are listed in
+    #[tokio::test]
+    async fn test_reorder_menus() {
+        let (config, path) = fresh_db_config("reorder-menus");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        for name in ["Cake", "Pasta", "Soup"] {
+            conn.execute("INSERT INTO menus (name) VALUES (?1)", [name])
+                .expect("Failed to insert menu data");
+        }
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/menus/reorder")
+            .json(&serde_json::json!({"menu_ids": [3, 1, 2]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+
+        let resp = warp::test::request().path("/menus").reply(&routes).await;
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        let names: Vec<_> = json_data
+            .as_array()
+            .expect("Expected a list of menus")
+            .iter()
+            .map(|menu| menu["name"].as_str().unwrap_or_default())
+            .collect();
+        assert_eq!(names, vec!["Soup", "Cake", "Pasta"]);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Error bodies are pretty-printed when enabled while success bodies stay compact
+    #[tokio::test]
+    async fn test_pretty_errors() {
+        let (config, path) = fresh_db_config("pretty-errors");
+        for pretty_errors in [true, false] {
+            let config = Arc::new(Config {
+                pretty_errors,
+                ..(*config).clone()
+            });
+            let routes = restaurant_routes(config, Instant::now());
+
+            let resp = warp::test::request()
+                .method("POST")
+                .path("/orders/create")
+                .json(&serde_json::json!({"table_id": 1, "menu_ids": []}))
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+            assert_eq!(resp.body().contains(&b'\n'), pretty_errors);
+            let json_data: serde_json::Value =
+                serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+            assert_eq!(json_data["error"].as_str(), Some("Please Add Items"));
+
+            let filter = warp::any()
+                .map(|| warp::reply::json(&serde_json::json!({"id": 1})))
+                .and(with_config(Arc::new(Config {
+                    pretty_errors,
+                    ..Config::default()
+                })))
+                .and_then(pretty_error_body);
+            let resp = warp::test::request().reply(&filter).await;
+            assert_eq!(resp.body().as_ref(), br#"{"id":1}"#);
+        }
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Accept headers allow JSON through exact, wildcard and non-zero quality ranges
+    #[test]
+    fn test_accepts_json() {
+        for accept in [
+            "application/json",
+            "Application/JSON; charset=utf-8",
+            "text/html, application/*;q=0.5",
+            "*/*",
+        ] {
+            assert!(accepts_json(accept), "{}", accept);
+        }
+        for accept in [
+            "application/xml",
+            "text/html, text/plain",
+            "application/json;q=0",
+            "",
+        ] {
+            assert!(!accepts_json(accept), "{}", accept);
+        }
+    }
+
+    // Test Case: An Accept header excluding JSON gets a 406 only when enforcement is on
+    #[tokio::test]
+    async fn test_enforce_accept_json() {
+        for (enforce_accept_json, status) in [
+            (true, warp::http::StatusCode::NOT_ACCEPTABLE),
+            (false, warp::http::StatusCode::OK),
+        ] {
+            let (config, path) = fresh_db_config("enforce-accept-json");
+            let config = Arc::new(Config {
+                enforce_accept_json,
+                ..(*config).clone()
+            });
+            let routes = restaurant_routes(config, Instant::now());
+            let resp = warp::test::request()
+                .path("/")
+                .header("accept", "application/xml")
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), status);
+            if enforce_accept_json {
+                let json_data: serde_json::Value =
+                    serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+                assert_eq!(
+                    json_data["error"].as_str(),
+                    Some("Only application/json is supported")
+                );
+            }
+
+            // JSON-compatible and missing Accept headers are always served
+            for accept in [Some("application/json"), Some("*/*"), None] {
+                let mut request = warp::test::request().path("/");
+                if let Some(accept) = accept {
+                    request = request.header("accept", accept);
+                }
+                let resp = request.reply(&routes).await;
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+            }
+            remove_db_files(&path);
+        }
+    }
+
+    // Test Case: A copied order has the same items as its source, which is left intact
+    #[tokio::test]
+    async fn test_copy_order() {
+        let (config, path) = fresh_db_config("copy-order");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        conn.execute("UPDATE order_items SET quantity = 3 WHERE menu_id = 2", [])
+            .expect("Failed to update quantity");
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/1/copy-to")
+            .json(&serde_json::json!({"table_id": 2}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+        assert_eq!(
+            resp.headers().get("location").and_then(|v| v.to_str().ok()),
+            Some("/orders/2")
+        );
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data["id"].as_i64(), Some(2));
+
+        // Compare the items of both orders, ignoring their own IDs
+        let items = |order_id: i64| {
+            let routes = routes.clone();
+            async move {
+                let resp = warp::test::request()
+                    .path(&format!("/orders/{}/items", order_id))
+                    .reply(&routes)
+                    .await;
+                let json_data: serde_json::Value =
+                    serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+                json_data
+                    .as_array()
+                    .expect("Expected a list of items")
+                    .iter()
+                    .map(|item| {
+                        (
+                            item["menu_id"].as_i64(),
+                            item["quantity"].as_i64(),
+                            item["cooking_time"].as_i64(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        let source_items = items(1).await;
+        assert_eq!(source_items.len(), 2);
+        assert_eq!(items(2).await, source_items);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Requests past MAX_CONCURRENT_REQUESTS get a 503 until a slot frees up
+    #[tokio::test]
+    async fn test_max_concurrent_requests() {
+        // The chaos delay keeps the first request in flight while the second arrives
+        let (config, path) = fresh_db_config("max-concurrent-requests");
+        let config = Arc::new(Config {
+            max_concurrent_requests: 1,
+            chaos_delay_ms: 200,
+            ..(*config).clone()
+        });
+        let routes = restaurant_routes(config, Instant::now());
+
+        let first = tokio::spawn({
+            let routes = routes.clone();
+            async move { warp::test::request().path("/").reply(&routes).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let resp = warp::test::request().path("/").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            resp.headers()
+                .get("Retry-After")
+                .and_then(|v| v.to_str().ok()),
+            Some("1")
+        );
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data["error"].as_str(), Some("Server busy"));
+
+        // Once the first request completes its slot is free again
+        assert_eq!(first.await.unwrap().status(), warp::http::StatusCode::OK);
+        let resp = warp::test::request().path("/").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Concurrent deletions of an order's last item delete it once and both succeed
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_last_item_deletes() {
+        let (config, path) = fresh_db_config("last-item-deletes");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        conn.execute("INSERT INTO orders (table_id) VALUES (1)", [])
+            .expect("Order creation failed");
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (1, 1, 6)",
+            [],
+        )
+        .expect("OrderItems creation failed");
+        let routes = restaurant_routes(config, Instant::now());
+
+        let mut tasks = Vec::new();
+        for _ in 0..2 {
+            let routes = routes.clone();
+            tasks.push(tokio::spawn(async move {
+                let resp = warp::test::request()
+                    .method("DELETE")
+                    .path("/orders/1/items/1")
+                    .reply(&routes)
+                    .await;
+                let json_data: serde_json::Value =
+                    serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+                (
+                    resp.status(),
+                    json_data["success"].as_str().map(String::from),
+                )
+            }));
+        }
+        let mut messages = Vec::new();
+        for task in tasks {
+            let (status, message) = task.await.unwrap();
+            assert_eq!(status, warp::http::StatusCode::OK);
+            messages.push(message.unwrap_or_default());
+        }
+        messages.sort();
+        assert_eq!(
+            messages,
+            vec![
+                "Menu already deleted",
+                "Menu deleted successfully and order deleted"
+            ]
+        );
+        assert_eq!(count_rows(&conn, "orders"), 0);
+        assert_eq!(count_rows(&conn, "order_items"), 0);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: The SQL dump rebuilds the same data in a fresh database, and is hidden unless enabled
+    #[tokio::test]
+    async fn test_dump_sql_round_trip() {
+        let (config, path) = fresh_db_config("dump-sql");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        conn.execute(
+            "INSERT INTO menus (name, position) VALUES (?1, 2)",
+            ["Chef's \"special\"; DROP TABLE menus"],
+        )
+        .expect("Failed to insert menu data");
+
+        let routes = restaurant_routes(config.clone(), Instant::now());
+        let resp = warp::test::request()
+            .path("/admin/dump.sql")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+
+        let routes = restaurant_routes(
+            Arc::new(Config {
+                admin_endpoints: true,
+                ..(*config).clone()
+            }),
+            Instant::now(),
+        );
+        let resp = warp::test::request()
+            .path("/admin/dump.sql")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let dump = String::from_utf8(resp.body().to_vec()).expect("Expected a UTF-8 dump");
+
+        // Load the dump into an empty database and compare every table
+        let restored = Connection::open_in_memory().expect("Failed to create test database");
+        restored.execute_batch(&dump).expect("Failed to load dump");
+        for query in [
+            "SELECT id, code FROM tables ORDER BY id",
+            "SELECT id, name, position FROM menus ORDER BY id",
+            "SELECT id, table_id FROM orders ORDER BY id",
+            "SELECT id, order_id, menu_id, cooking_time, quantity FROM order_items ORDER BY id",
+        ] {
+            let rows = |conn: &Connection| -> Vec<String> {
+                let mut stmt = conn.prepare(query).expect("Failed to prepare query");
+                let column_count = stmt.column_count();
+                stmt.query_map([], |row| {
+                    let values: Vec<String> = (0..column_count)
+                        .map(|index| format!("{:?}", row.get_ref(index).unwrap()))
+                        .collect();
+                    Ok(values.join("|"))
+                })
+                .expect("Failed to run query")
+                .collect::<Result<_, _>>()
+                .expect("Failed to read rows")
+            };
+            assert!(!rows(&conn).is_empty(), "{}", query);
+            assert_eq!(rows(&restored), rows(&conn), "{}", query);
+        }
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Preflight requests from an allowed origin get CORS headers, others are refused
+    #[tokio::test]
+    async fn test_cors_preflight() {
+        let (config, path) = fresh_db_config("cors-preflight");
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("OPTIONS")
+            .path("/tables")
+            .header("origin", "http://localhost:3000")
+            .header("access-control-request-method", "POST")
+            .header("access-control-request-headers", "content-type")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(
+            resp.headers()
+                .get("access-control-allow-origin")
+                .and_then(|v| v.to_str().ok()),
+            Some("http://localhost:3000")
+        );
+
+        // The method override and request timeout headers may be sent cross-origin
+        let resp = warp::test::request()
+            .method("OPTIONS")
+            .path("/tables/1/items/1")
+            .header("origin", "http://localhost:3000")
+            .header("access-control-request-method", "POST")
+            .header(
+                "access-control-request-headers",
+                "x-http-method-override, x-request-timeout-ms",
+            )
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let allowed_headers = resp
+            .headers()
+            .get("access-control-allow-headers")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        for header in ["x-http-method-override", "x-request-timeout-ms"] {
+            assert!(allowed_headers.contains(header), "{}", allowed_headers);
+        }
+
+        // Plain requests from the origin get the header too
+        let resp = warp::test::request()
+            .path("/tables")
+            .header("origin", "http://localhost:3000")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert!(resp.headers().contains_key("access-control-allow-origin"));
+
+        let resp = warp::test::request()
+            .method("OPTIONS")
+            .path("/tables")
+            .header("origin", "https://elsewhere.example.com")
+            .header("access-control-request-method", "POST")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::FORBIDDEN);
+        assert!(!resp.headers().contains_key("access-control-allow-origin"));
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Orders can give a quantity per item alongside the plain menu_ids list
+    #[tokio::test]
+    async fn test_create_order_item_quantity() {
+        let (config, path) = fresh_db_config("order-item-quantity");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({"table_id": 1, "items": [{"menu_id": 1, "quantity": 3}]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+        let (quantity, cooking_time): (i64, i64) = conn
+            .query_row(
+                "SELECT quantity, cooking_time FROM order_items WHERE order_id = 1 AND menu_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("Order item missing");
+        assert_eq!(quantity, 3);
+        // The per-unit cooking time is scaled by the quantity
+        assert_eq!(cooking_time % 3, 0);
+        assert!((15..=45).contains(&cooking_time));
+
+        // Both shapes can be combined and add to the existing item
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({
+                "table_id": 1,
+                "menu_ids": [1],
+                "items": [{"menu_id": 1, "quantity": 2}]
+            }))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let (quantity, new_cooking_time): (i64, i64) = conn
+            .query_row(
+                "SELECT quantity, cooking_time FROM order_items WHERE order_id = 1 AND menu_id = 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("Order item missing");
+        assert_eq!(quantity, 6);
+        assert_eq!(new_cooking_time, cooking_time * 2);
+
+        // A quantity below 1 is rejected
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({"table_id": 1, "items": [{"menu_id": 2, "quantity": 0}]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+        assert_eq!(count_rows(&conn, "order_items"), 1);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: A retried order with the same Idempotency-Key gets the original response and adds nothing
+    #[tokio::test]
+    async fn test_create_order_idempotency_key() {
+        let (config, path) = fresh_db_config("order-idempotency-key");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let create = |key: &str| {
+            warp::test::request()
+                .method("POST")
+                .path("/orders/create")
+                .header("idempotency-key", key)
+                .json(&serde_json::json!({"table_id": 1, "items": [{"menu_id": 1, "quantity": 2}]}))
+        };
+
+        let first = create("retry-1").reply(&routes).await;
+        assert_eq!(first.status(), warp::http::StatusCode::CREATED);
+        let retry = create("retry-1").reply(&routes).await;
+        assert_eq!(retry.status(), warp::http::StatusCode::CREATED);
+        assert_eq!(
+            first
+                .headers()
+                .get("location")
+                .and_then(|v| v.to_str().ok()),
+            Some("/orders/1")
+        );
+        assert_eq!(
+            retry.headers().get("location"),
+            first.headers().get("location")
+        );
+        assert_eq!(retry.body(), first.body());
+        let quantity: i64 = conn
+            .query_row(
+                "SELECT quantity FROM order_items WHERE order_id = 1 AND menu_id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .expect("Order item missing");
+        assert_eq!(quantity, 2);
+        assert_eq!(count_rows(&conn, "order_items"), 1);
+
+        // Reusing the key for a different request is refused without replaying or adding anything
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .header("idempotency-key", "retry-1")
+            .json(&serde_json::json!({"table_id": 2, "items": [{"menu_id": 1, "quantity": 2}]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::UNPROCESSABLE_ENTITY);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(
+            json_data["error"].as_str(),
+            Some("Idempotency-Key was already used for a different request")
+        );
+        assert_eq!(count_rows(&conn, "orders"), 1);
+
+        // A new key adds the items again
+        let resp = create("retry-2").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let order: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(order["menus"][0]["quantity"], 4);
+
+        // Once its TTL has passed, a key is processed like a new one
+        conn.execute(
+            "UPDATE idempotency_keys SET created_at = created_at - 86400",
+            [],
+        )
+        .expect("Failed to age the keys");
+        let resp = create("retry-1").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let order: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(order["menus"][0]["quantity"], 6);
+        assert_eq!(count_rows(&conn, "idempotency_keys"), 1);
+
+        // An empty key is rejected
+        let resp = create("").reply(&routes).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: A websocket client gets the current orders, then an event for each order change
+    #[tokio::test]
+    async fn test_ws_orders() {
+        let (config, path) = fresh_db_config("ws-orders");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let mut client = warp::test::ws()
+            .path("/ws/orders")
+            .handshake(routes.clone())
+            .await
+            .expect("Websocket handshake failed");
+        let next_json = |message: warp::ws::Message| -> serde_json::Value {
+            serde_json::from_str(message.to_str().expect("Expected a text message"))
+                .expect("Expected a JSON message")
+        };
+
+        // The current orders come first
+        let orders = next_json(client.recv().await.expect("Expected the order list"));
+        assert_eq!(orders.as_array().map(Vec::len), Some(1));
+        assert_eq!(orders[0]["table_id"], 1);
+
+        // Creating an order over HTTP pushes an event
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({"table_id": 2, "menu_ids": [3]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+        let event = next_json(client.recv().await.expect("Expected an event"));
+        assert_eq!(
+            event,
+            serde_json::json!({"event": "created", "order_id": 2})
+        );
+
+        // Deleting it does too
+        let resp = warp::test::request()
+            .method("DELETE")
+            .path("/orders/2")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let event = next_json(client.recv().await.expect("Expected an event"));
+        assert_eq!(
+            event,
+            serde_json::json!({"event": "deleted", "order_id": 2})
+        );
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: An order starts pending, moves to another valid status and rejects unknown ones
+    #[tokio::test]
+    async fn test_order_status_transitions() {
+        let (config, path) = fresh_db_config("order-status");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let order_status = || async {
+            let resp = warp::test::request().path("/orders").reply(&routes).await;
+            let json_data: serde_json::Value =
+                serde_json::from_slice(resp.body()).expect("Failed to parse orders");
+            json_data[0]["status"].as_str().map(str::to_string)
+        };
+        assert_eq!(order_status().await.as_deref(), Some("pending"));
+
+        let resp = warp::test::request()
+            .method("PATCH")
+            .path("/orders/1/status")
+            .json(&serde_json::json!({"status": "cooking"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(order_status().await.as_deref(), Some("cooking"));
+
+        // Unknown statuses and orders are rejected and leave the status alone
+        for (order_id, status, expected) in [
+            (1, "delivered", warp::http::StatusCode::BAD_REQUEST),
+            (1, "", warp::http::StatusCode::BAD_REQUEST),
+            (99, "served", warp::http::StatusCode::NOT_FOUND),
+        ] {
+            let resp = warp::test::request()
+                .method("PATCH")
+                .path(&format!("/orders/{}/status", order_id))
+                .json(&serde_json::json!({"status": status}))
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), expected, "{}", status);
+            if expected == warp::http::StatusCode::BAD_REQUEST {
+                let json_data: serde_json::Value =
+                    serde_json::from_slice(resp.body()).expect("Failed to parse error");
+                assert_eq!(json_data["error"].as_str(), Some("Invalid status"));
+            }
+        }
+        assert_eq!(order_status().await.as_deref(), Some("cooking"));
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: A cancelled order releases its table, so a new order for it starts afresh
+    #[tokio::test]
+    async fn test_create_order_after_cancel() {
+        let (config, path) = fresh_db_config("order-after-cancel");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("PATCH")
+            .path("/orders/1/status")
+            .json(&serde_json::json!({"status": "cancelled"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        // The cancelled order no longer occupies the table
+        let resp = warp::test::request()
+            .path("/tables/occupied")
+            .reply(&routes)
+            .await;
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Failed to parse tables");
+        assert_eq!(json_data, serde_json::json!([]));
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({"table_id": 1, "items": [{"menu_id": 3, "quantity": 1}]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+        let order_id: i64 = conn
+            .query_row("SELECT id FROM orders WHERE table_id = 1", [], |row| {
+                row.get(0)
+            })
+            .expect("Failed to read the new order");
+        assert_eq!(count_rows(&conn, "orders"), 1);
+
+        // The new order is pending and holds only the new item
+        let resp = warp::test::request()
+            .path(&format!("/orders/{}", order_id))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Failed to parse order");
+        assert_eq!(json_data["status"].as_str(), Some("pending"));
+        let resp = warp::test::request()
+            .path("/tables/1/items")
+            .reply(&routes)
+            .await;
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Failed to parse items");
+        let menu_ids: Vec<i64> = json_data
+            .as_array()
+            .expect("Items should be an array")
+            .iter()
+            .filter_map(|item| item["menu_id"].as_i64())
+            .collect();
+        assert_eq!(menu_ids, vec![3]);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: The deep health check passes without leaving rows behind and fails on a read-only database
+    #[tokio::test]
+    async fn test_deep_health_check() {
+        let (config, path) = fresh_db_config("deep-health");
+        let routes = restaurant_routes(config.clone(), Instant::now());
+        for _ in 0..3 {
+            let resp = warp::test::request()
+                .path("/healthz/deep")
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        }
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        assert_eq!(count_rows(&conn, "healthcheck"), 0);
+
+        // A read-only connection can't write the sentinel row
+        let read_only = r2d2::Pool::new(
+            r2d2_sqlite::SqliteConnectionManager::file(&config.db_path)
+                .with_flags(rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY),
+        )
+        .expect("Failed to open read-only pool");
+        let resp = warp::test::request()
+            .path("/healthz/deep")
+            .reply(&deep_health_route(read_only))
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::SERVICE_UNAVAILABLE);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Failed to parse health check");
+        assert_eq!(json_data["status"].as_str(), Some("unavailable"));
+        assert!(json_data["error"]
+            .as_str()
+            .is_some_and(|reason| reason.contains("readonly")));
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: The order listing pages through orders by ID
+    #[tokio::test]
+    async fn test_list_orders_pagination() {
+        let (config, path) = fresh_db_config("orders-pagination");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        for code in ["T-04", "T-05"] {
+            conn.execute("INSERT INTO tables (code) VALUES (?1)", [code])
+                .expect("Failed to insert table data");
+        }
+        for table_id in 1..=5 {
+            conn.execute("INSERT INTO orders (table_id) VALUES (?1)", [table_id])
+                .expect("Order creation failed");
+        }
+        let routes = restaurant_routes(config, Instant::now());
+        let order_ids = |query: &'static str| {
+            let routes = routes.clone();
+            async move {
+                let resp = warp::test::request()
+                    .path(&format!("/orders{}", query))
+                    .reply(&routes)
+                    .await;
+                assert_eq!(resp.status(), warp::http::StatusCode::OK, "{}", query);
+                let json_data: serde_json::Value =
+                    serde_json::from_slice(resp.body()).expect("Failed to parse orders");
+                json_data
+                    .as_array()
+                    .expect("Expected an array of orders")
+                    .iter()
+                    .filter_map(|order| order["id"].as_i64())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(order_ids("?limit=2&offset=2").await, vec![3, 4]);
+        assert_eq!(order_ids("").await, vec![1, 2, 3, 4, 5]);
+        assert_eq!(order_ids("?offset=4").await, vec![5]);
+        assert_eq!(order_ids("?limit=1000").await.len(), 5);
+
+        // Negative values aren't valid page bounds
+        let resp = warp::test::request()
+            .path("/orders?offset=-1")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: A calibrated cooking time applies to new order items but not existing ones
+    #[tokio::test]
+    async fn test_menu_cooking_time_applies_to_new_items() {
+        let (config, path) = fresh_db_config("menu-cooking-time");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+
+        // Out of bounds times and unknown menus are rejected
+        for (menu_id, cooking_time, status) in [
+            (1, 0, warp::http::StatusCode::BAD_REQUEST),
+            (1, -5, warp::http::StatusCode::BAD_REQUEST),
+            (1, 121, warp::http::StatusCode::BAD_REQUEST),
+            (99, 42, warp::http::StatusCode::NOT_FOUND),
+        ] {
+            let resp = warp::test::request()
+                .method("PATCH")
+                .path(&format!("/menus/{}/cooking-time", menu_id))
+                .json(&serde_json::json!({"cooking_time": cooking_time}))
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), status);
+        }
+
+        let resp = warp::test::request()
+            .method("PATCH")
+            .path("/menus/1/cooking-time")
+            .json(&serde_json::json!({"cooking_time": 42}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({"table_id": 2, "menu_ids": [1]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+
+        let cooking_time = |table_id: i64| {
+            conn.query_row(
+                "SELECT oi.cooking_time FROM order_items oi JOIN orders o ON oi.order_id = o.id
+                WHERE o.table_id = ?1 AND oi.menu_id = 1",
+                [table_id],
+                |row| row.get::<_, i64>(0),
+            )
+            .expect("Failed to read cooking time")
+        };
+        // The existing order keeps its snapshot, the new one uses the calibrated time
+        assert_eq!(cooking_time(1), 6);
+        assert_eq!(cooking_time(2), 42);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: An item inherits the cooking time configured when its menu was created
+    #[tokio::test]
+    async fn test_menu_created_with_cooking_time() {
+        let (config, path) = fresh_db_config("menu-created-cooking-time");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+
+        // Out of bounds times are rejected
+        for cooking_time in [0, -5, 121] {
+            let resp = warp::test::request()
+                .method("POST")
+                .path("/menus/create")
+                .json(&serde_json::json!({"name": "Ramen", "cooking_time": cooking_time}))
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), warp::http::StatusCode::BAD_REQUEST);
+        }
+        assert_eq!(count_rows(&conn, "menus"), 5);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/menus/create")
+            .json(&serde_json::json!({"name": "Ramen", "cooking_time": 37}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let menu_id = body["id"].as_i64().unwrap();
+
+        // Every table ordering the menu gets exactly the configured time
+        for table_id in [1, 2] {
+            let resp = warp::test::request()
+                .method("POST")
+                .path("/orders/create")
+                .json(&serde_json::json!({"table_id": table_id, "menu_ids": [menu_id]}))
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+        }
+        let mut stmt = conn
+            .prepare("SELECT cooking_time FROM order_items WHERE menu_id = ?1")
+            .expect("Failed to prepare statement");
+        let cooking_times: Vec<i64> = stmt
+            .query_map([menu_id], |row| row.get(0))
+            .expect("Failed to read cooking times")
+            .collect::<Result<_, _>>()
+            .expect("Failed to read cooking times");
+        assert_eq!(cooking_times, vec![37, 37]);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: /v2 uses camelCase fields in requests and responses while / and /v1 keep snake_case
+    #[tokio::test]
+    async fn test_v2_camel_case_fields() {
+        let (config, path) = fresh_db_config("v2-camel-case");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let get_json = |path: &'static str| {
+            let routes = routes.clone();
+            async move {
+                let resp = warp::test::request().path(path).reply(&routes).await;
+                assert_eq!(resp.status(), warp::http::StatusCode::OK);
+                serde_json::from_slice::<serde_json::Value>(resp.body())
+                    .expect("Expected a JSON body")
+            }
+        };
+
+        let snake = get_json("/orders/1/items").await;
+        assert_eq!(get_json("/v1/orders/1/items").await, snake);
+        assert!(snake[0]["menu_id"].is_i64() && snake[0]["cooking_time"].is_i64());
+        let camel = get_json("/v2/orders/1/items").await;
+        assert!(camel[0]["menuId"].is_i64() && camel[0]["cookingTime"].is_i64());
+        assert!(camel[0].get("menu_id").is_none());
+        assert_eq!(camel[0]["menuName"], snake[0]["menu_name"]);
+
+        // Request bodies are read in camelCase too
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/v2/orders/create")
+            .json(&serde_json::json!({"tableId": 2, "menuIds": [1]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert!(json_data["id"].is_i64());
+
+        // Menu names keying the ensure response are data and stay as they are
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/v2/menus/ensure")
+            .json(&serde_json::json!({"names": ["fish_and_chips"]}))
+            .reply(&routes)
+            .await;
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert!(json_data["fish_and_chips"].is_i64());
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: The trigger-maintained order summary matches freshly computed totals after a series of changes
+    #[tokio::test]
+    async fn test_order_summary_consistency() {
+        let (config, path) = fresh_db_config("order-summary");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let request = |method: &'static str, path: String, body: serde_json::Value| {
+            let routes = routes.clone();
+            async move {
+                let resp = warp::test::request()
+                    .method(method)
+                    .path(&path)
+                    .json(&body)
+                    .reply(&routes)
+                    .await;
+                assert!(resp.status().is_success(), "{} {}", method, path);
+            }
+        };
+
+        // Create, grow, shrink, copy and empty orders
+        let create = |table_id: i64, menu_ids: &[i64]| {
+            request(
+                "POST",
+                "/orders/create".to_string(),
+                serde_json::json!({"table_id": table_id, "menu_ids": menu_ids}),
+            )
+        };
+        create(1, &[1, 2, 2, 3]).await;
+        create(2, &[4]).await;
+        create(1, &[3, 5]).await;
+        request(
+            "DELETE",
+            "/orders/1/items/2".to_string(),
+            serde_json::Value::Null,
+        )
+        .await;
+        request(
+            "POST",
+            "/orders/1/copy-to".to_string(),
+            serde_json::json!({"table_id": 3}),
+        )
+        .await;
+        request(
+            "DELETE",
+            "/orders/2/items/4".to_string(),
+            serde_json::Value::Null,
+        )
+        .await;
+        conn.execute(
+            "UPDATE order_items SET quantity = 4, cooking_time = 40 WHERE id = 1",
+            [],
+        )
+        .expect("Failed to update order item");
+        conn.execute("UPDATE tables SET code = 'T-33' WHERE id = 3", [])
+            .expect("Failed to rename table");
+
+        let resp = warp::test::request()
+            .path("/orders/summary")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let summaries: Vec<OrderSummary> =
+            serde_json::from_slice(resp.body()).expect("Expected a list of summaries");
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT orders.id, t.code, COALESCE(SUM(oi.cooking_time), 0), COUNT(oi.id), COALESCE(SUM(oi.quantity), 0)
+                FROM orders
+                LEFT JOIN tables AS t ON t.id = orders.table_id
+                LEFT JOIN order_items AS oi ON oi.order_id = orders.id
+                GROUP BY orders.id
+                ORDER BY orders.id",
+            )
+            .expect("Failed to prepare query");
+        let expected: Vec<OrderSummary> = stmt
+            .query_map([], |row| {
+                Ok(OrderSummary {
+                    order_id: row.get(0)?,
+                    table_code: row.get(1)?,
+                    total_cooking_time: row.get(2)?,
+                    item_count: row.get(3)?,
+                    total_quantity: row.get(4)?,
+                })
+            })
+            .expect("Failed to run query")
+            .collect::<Result<_, _>>()
+            .expect("Failed to read rows");
+        // Table 2's order was emptied and deleted, the other two remain
+        assert_eq!(expected.len(), 2);
+        assert_eq!(expected[1].table_code.as_deref(), Some("T-33"));
+        assert_eq!(summaries, expected);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Raising an item's quantity and removing units again restores its exact unit cooking time
+    #[tokio::test]
+    async fn test_unit_cooking_time_survives_quantity_changes() {
+        let (config, path) = fresh_db_config("unit-cooking-time");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        conn.execute("UPDATE menus SET cooking_time = 7 WHERE id = 1", [])
+            .expect("Failed to calibrate menu");
+        let routes = restaurant_routes(config, Instant::now());
+        let item = || async {
+            let resp = warp::test::request()
+                .path("/tables/1/items/1")
+                .reply(&routes)
+                .await;
+            let json_data: serde_json::Value =
+                serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+            (
+                json_data["quantity"].as_i64(),
+                json_data["cooking_time"].as_i64(),
+            )
+        };
+
+        for menu_ids in [vec![1], vec![1, 1]] {
+            let resp = warp::test::request()
+                .method("POST")
+                .path("/orders/create")
+                .json(&serde_json::json!({"table_id": 1, "menu_ids": menu_ids}))
+                .reply(&routes)
+                .await;
+            assert!(resp.status().is_success());
+        }
+        assert_eq!(item().await, (Some(3), Some(21)));
+
+        for expected in [(Some(2), Some(14)), (Some(1), Some(7))] {
+            let resp = warp::test::request()
+                .method("DELETE")
+                .path("/orders/1/items/1")
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), warp::http::StatusCode::OK);
+            assert_eq!(item().await, expected);
+        }
+
+        // An item written with only a total, e.g. by an older version, gets its unit time derived
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time, quantity) VALUES (1, 2, 10, 2)",
+            [],
+        )
+        .expect("OrderItems creation failed");
+        let unit_cooking_time: i64 = conn
+            .query_row(
+                "SELECT unit_cooking_time FROM order_items WHERE menu_id = 2",
+                [],
+                |row| row.get(0),
+            )
+            .expect("Order item missing");
+        assert_eq!(unit_cooking_time, 5);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Clearing a table's order deletes all of its items and the order itself
+    #[tokio::test]
+    async fn test_clear_order() {
+        let (config, path) = fresh_db_config("clear-order");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (1, 3, 8)",
+            [],
+        )
+        .expect("OrderItems creation failed");
+        // Table 2 has an order of its own that must be left alone
+        conn.execute("INSERT INTO orders (table_id) VALUES (2)", [])
+            .expect("Order creation failed");
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (2, 1, 5)",
+            [],
+        )
+        .expect("OrderItems creation failed");
+        assert_eq!(count_rows(&conn, "order_items"), 4);
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("DELETE")
+            .path("/tables/1/items")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data, serde_json::json!({"success": "Order cleared"}));
+        // Only table 2's order and its single item remain
+        assert_eq!(count_rows(&conn, "order_items"), 1);
+        let table_ids: Vec<i64> = conn
+            .prepare("SELECT table_id FROM orders")
+            .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect())
+            .expect("Failed to read orders");
+        assert_eq!(table_ids, vec![2]);
+
+        // Clearing again finds no active order
+        let resp = warp::test::request()
+            .method("DELETE")
+            .path("/tables/1/items")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: DELETE /orders/{order_id} cancels that order with its items, and a missing order is NOT FOUND
+    #[tokio::test]
+    async fn test_delete_order() {
+        let (config, path) = fresh_db_config("delete-order");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        // Table 2 has an order of its own that must be left alone
+        conn.execute("INSERT INTO orders (table_id) VALUES (2)", [])
+            .expect("Order creation failed");
+        conn.execute(
+            "INSERT INTO order_items (order_id, menu_id, cooking_time) VALUES (2, 1, 5)",
+            [],
+        )
+        .expect("OrderItems creation failed");
+        let routes = restaurant_routes(config, Instant::now());
+
+        let resp = warp::test::request()
+            .method("DELETE")
+            .path("/orders/1")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data, serde_json::json!({"success": "Order deleted"}));
+        assert_eq!(count_rows(&conn, "orders"), 1);
+        assert_eq!(count_rows(&conn, "order_items"), 1);
+
+        // The deleted order and one that never existed are both not found
+        for missing in ["/orders/1", "/orders/999"] {
+            let resp = warp::test::request()
+                .method("DELETE")
+                .path(missing)
+                .reply(&routes)
+                .await;
+            assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+            let json_data: serde_json::Value =
+                serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+            assert_eq!(json_data["error"], "Order not found");
+        }
+        assert_eq!(count_rows(&conn, "orders"), 1);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: PATCH sets an item's quantity and cooking time, and 0 removes the item and then the order
+    #[tokio::test]
+    async fn test_set_item_quantity() {
+        let (config, path) = fresh_db_config("set-item-quantity");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let item = |menu_id: i64| -> (i64, i64) {
+            conn.query_row(
+                "SELECT quantity, cooking_time FROM order_items WHERE order_id = 1 AND menu_id = ?1",
+                [menu_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .expect("Failed to read order item")
+        };
+        let set_quantity = |menu_id: i64, quantity: i64| {
+            warp::test::request()
+                .method("PATCH")
+                .path(&format!("/tables/1/items/{}", menu_id))
+                .json(&serde_json::json!({ "quantity": quantity }))
+                .reply(&routes)
+        };
+
+        // Raising the quantity scales the cooking time with it
+        let resp = set_quantity(1, 3).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(
+            json_data,
+            serde_json::json!({"success": "Item quantity updated"})
+        );
+        assert_eq!(item(1), (3, 18));
+
+        // Lowering it does too
+        let resp = set_quantity(1, 2).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(item(1), (2, 12));
+
+        // Negative quantities and items not in the order are refused
+        assert_eq!(
+            set_quantity(1, -1).await.status(),
+            warp::http::StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            set_quantity(3, 2).await.status(),
+            warp::http::StatusCode::NOT_FOUND
+        );
+
+        // 0 deletes the item, and the order once it has none left
+        let resp = set_quantity(1, 0).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data, serde_json::json!({"success": "Item deleted"}));
+        assert_eq!(count_rows(&conn, "order_items"), 1);
+        let resp = set_quantity(2, 0).await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(
+            json_data,
+            serde_json::json!({"success": "Item deleted and order deleted"})
+        );
+        assert_eq!(count_rows(&conn, "order_items"), 0);
+        assert_eq!(count_rows(&conn, "orders"), 0);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Marking an item served shows in later reads, and new units of it are unserved again
+    #[tokio::test]
+    async fn test_mark_item_served() {
+        let (config, path) = fresh_db_config("mark-item-served");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let get_json = |path: &'static str| {
+            let routes = routes.clone();
+            async move {
+                let resp = warp::test::request().path(path).reply(&routes).await;
+                assert_eq!(resp.status(), warp::http::StatusCode::OK, "{}", path);
+                serde_json::from_slice::<serde_json::Value>(resp.body())
+                    .expect("Expected a JSON body")
+            }
+        };
+        assert_eq!(get_json("/tables/1/items/1").await["served"], false);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/tables/1/items/1/serve")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(get_json("/tables/1/items/1").await["served"], true);
+        let items = get_json("/tables/1/items").await;
+        assert_eq!(
+            (items[0]["served"].clone(), items[1]["served"].clone()),
+            (serde_json::json!(true), serde_json::json!(false))
+        );
+        assert_eq!(get_json("/orders/1").await["menus"][0]["served"], true);
+
+        // Ordering more of a served item leaves units to deliver
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/orders/create")
+            .json(&serde_json::json!({"table_id": 1, "menu_ids": [1]}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::OK);
+        assert_eq!(get_json("/tables/1/items/1").await["served"], false);
+
+        // Items not in the table's order are not found
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/tables/2/items/1/serve")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Oversized bodies to the create routes are refused with a 413
+    #[tokio::test]
+    async fn test_create_routes_body_limit() {
+        let (config, path) = fresh_db_config("body-limit");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        let routes = restaurant_routes(config, Instant::now());
+        let oversized = serde_json::json!({"code": "x".repeat(MAX_CREATE_BODY_BYTES as usize)});
+        for create_path in ["/tables/create", "/menus/create", "/orders/create"] {
+            let resp = warp::test::request()
+                .method("POST")
+                .path(create_path)
+                .json(&oversized)
+                .reply(&routes)
+                .await;
+            assert_eq!(
+                resp.status(),
+                warp::http::StatusCode::PAYLOAD_TOO_LARGE,
+                "{}",
+                create_path
+            );
+            let json_data: serde_json::Value =
+                serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+            assert_eq!(json_data["error"].as_str(), Some("Payload too large"));
+        }
+        assert_eq!(count_rows(&conn, "tables"), 0);
+
+        // Bodies under the limit are still accepted
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/tables/create")
+            .json(&serde_json::json!({"code": "T-01"}))
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::CREATED);
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Each API error answers its status with an error message and code
+    #[tokio::test]
+    async fn test_api_error_rejections() {
+        for (api_error, status, message, code) in [
+            (
+                ApiError::NotFound("No Item Found".to_string()),
+                warp::http::StatusCode::NOT_FOUND,
+                "No Item Found",
+                "not_found",
+            ),
+            (
+                ApiError::Conflict("Menu is used in active orders".to_string()),
+                warp::http::StatusCode::CONFLICT,
+                "Menu is used in active orders",
+                "conflict",
+            ),
+            (
+                ApiError::BadRequest("Bad table IDs".to_string()),
+                warp::http::StatusCode::BAD_REQUEST,
+                "Bad table IDs",
+                "bad_request",
+            ),
+            (
+                ApiError::Integrity("Quantity must not be negative".to_string()),
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Quantity must not be negative",
+                "integrity_violation",
+            ),
+            (
+                ApiError::Internal,
+                warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error",
+                "internal",
+            ),
+        ] {
+            let resp = handle_rejection(warp::reject::custom(api_error))
+                .await
+                .expect("Rejections should be recovered");
+            assert_eq!(resp.status(), status);
+            let json_data = convert_response_to_json(resp).await;
+            assert_eq!(
+                json_data,
+                serde_json::json!({"error": message, "code": code})
+            );
+        }
+
+        // Handler errors reach the client through the routes
+        let (config, path) = fresh_db_config("api-error");
+        let conn = get_db_conn(&config.db_path, config.db_busy_timeout())
+            .expect("Failed to open SQLite connection");
+        setup_static_data(&conn);
+        setup_order(&conn);
+        let routes = restaurant_routes(config, Instant::now());
+        let resp = warp::test::request()
+            .path("/tables/1/items/5")
+            .reply(&routes)
+            .await;
+        assert_eq!(resp.status(), warp::http::StatusCode::NOT_FOUND);
+        let json_data: serde_json::Value =
+            serde_json::from_slice(resp.body()).expect("Expected a JSON body");
+        assert_eq!(json_data["code"].as_str(), Some("not_found"));
+        assert_eq!(json_data["error"].as_str(), Some("No Item Found"));
+
+        remove_db_files(&path);
+    }
+
+    // Test Case: Unhandled rejections answer a sanitized 500 with a request ID instead of their debug output
+    #[tokio::test]
+    async fn test_unhandled_rejection_is_sanitized() {
+        #[derive(Debug)]
+        struct SecretFailure;
+        impl warp::reject::Reject for SecretFailure {}
+
+        let resp = handle_rejection(warp::reject::custom(SecretFailure))
+            .await
+            .expect("Rejections should be recovered");
+        assert_eq!(resp.status(), warp::http::StatusCode::INTERNAL_SERVER_ERROR);
+        let json_data = convert_response_to_json(resp).await;
+        assert_eq!(json_data["error"].as_str(), Some("Internal server error"));
+        let request_id = json_data["request_id"]
+            .as_str()
+            .expect("Expected a request ID");
+        assert_eq!(request_id.len(), 16);
+        assert!(!json_data.to_string().contains("SecretFailure"));
+    }
 }
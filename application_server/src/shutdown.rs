@@ -0,0 +1,124 @@
+// src/shutdown.rs
+use std::future::Future;
+
+/// Resolves once the process is asked to stop with SIGINT (Ctrl-C) or, on unix, SIGTERM.
+/// Passed to the server's graceful shutdown, which then stops accepting connections
+/// and waits for in-flight requests to complete.
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %err, "failed to listen for SIGINT");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    wait_for_shutdown(interrupt, terminate).await
+}
+
+/// Wait for whichever shutdown signal arrives first and log that the server is stopping
+async fn wait_for_shutdown(
+    interrupt: impl Future<Output = ()>,
+    terminate: impl Future<Output = ()>,
+) {
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+    println!("Shutting down");
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::tests::remove_db_files;
+    use crate::routes::restaurant_routes;
+    use std::convert::Infallible;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+    use warp::hyper::service::make_service_fn;
+    use warp::hyper::Server;
+
+    // Test Case: Either signal ends the wait, and nothing else does
+    #[tokio::test]
+    async fn test_wait_for_shutdown() {
+        let pending = std::future::pending::<()>;
+        wait_for_shutdown(async {}, pending()).await;
+        wait_for_shutdown(pending(), async {}).await;
+        let waiting = tokio::time::timeout(
+            Duration::from_millis(50),
+            wait_for_shutdown(pending(), pending()),
+        )
+        .await;
+        assert!(waiting.is_err());
+    }
+
+    // Test Case: A request in flight when shutdown starts still gets its response
+    #[tokio::test]
+    async fn test_graceful_shutdown_completes_in_flight_requests() {
+        let path = std::env::temp_dir().join("restaurant-test-graceful-shutdown.db");
+        remove_db_files(&path);
+        crate::db::initialize_db(
+            path.to_str().expect("Temp path should be UTF-8"),
+            Config::default().db_busy_timeout(),
+        );
+        // Every request takes a while, so the one below is still running at shutdown
+        let config = Arc::new(Config {
+            db_path: path.to_string_lossy().into_owned(),
+            chaos_delay_ms: 300,
+            ..Config::default()
+        });
+        let service = warp::service(restaurant_routes(config, Instant::now()));
+        let make_service = make_service_fn(move |_| {
+            let service = service.clone();
+            async move { Ok::<_, Infallible>(service) }
+        });
+        let (stop, stopped) = oneshot::channel::<()>();
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = server.local_addr();
+        let server = tokio::spawn(server.with_graceful_shutdown(async {
+            let _ = stopped.await;
+        }));
+
+        let mut stream = tokio::net::TcpStream::connect(addr)
+            .await
+            .expect("Failed to connect");
+        stream
+            .write_all(b"GET /tables HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .expect("Failed to send request");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        stop.send(()).expect("Server already stopped");
+
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .await
+            .expect("Failed to read response");
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        server
+            .await
+            .expect("Server task panicked")
+            .expect("Server failed");
+
+        remove_db_files(&path);
+    }
+}
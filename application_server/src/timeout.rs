@@ -0,0 +1,161 @@
+// src/timeout.rs
+use crate::config::Config;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use warp::http::{HeaderMap, Request, Response, StatusCode};
+use warp::hyper::service::Service;
+use warp::hyper::Body;
+use warp::Reply;
+
+/// Header a client sets to choose its own processing deadline, in milliseconds
+pub const REQUEST_TIMEOUT_HEADER: &str = "x-request-timeout-ms";
+
+/// Service answering 504 when the wrapped service takes longer than the request's timeout.
+/// It wraps the whole filter chain, since a warp filter can't time out the filters after it.
+#[derive(Debug, Clone)]
+pub struct RequestTimeout<S> {
+    inner: S,
+    default_timeout: Option<Duration>,
+    max_timeout: Duration,
+}
+
+impl<S> RequestTimeout<S> {
+    /// Wrap `inner` with the `REQUEST_TIMEOUT_MS` default and `MAX_REQUEST_TIMEOUT_MS` cap
+    pub fn new(config: &Config, inner: S) -> RequestTimeout<S> {
+        RequestTimeout {
+            inner,
+            default_timeout: (config.request_timeout_ms > 0)
+                .then(|| Duration::from_millis(config.request_timeout_ms)),
+            max_timeout: Duration::from_millis(config.max_request_timeout_ms),
+        }
+    }
+
+    /// Timeout for a request: its `X-Request-Timeout-Ms` value capped at the maximum,
+    /// or the default when the header is missing or isn't a positive number of milliseconds
+    fn timeout_for(&self, headers: &HeaderMap) -> Option<Duration> {
+        let requested = headers
+            .get(REQUEST_TIMEOUT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .filter(|millis| *millis > 0);
+        match requested {
+            Some(millis) => Some(Duration::from_millis(millis).min(self.max_timeout)),
+            None => self.default_timeout,
+        }
+    }
+}
+
+impl<S> Service<Request<Body>> for RequestTimeout<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response<Body>, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let timeout = self.timeout_for(req.headers());
+        let response = self.inner.call(req);
+        Box::pin(async move {
+            let Some(timeout) = timeout else {
+                return response.await;
+            };
+            match tokio::time::timeout(timeout, response).await {
+                Ok(response) => response,
+                // Dropping the unfinished response future abandons the request's remaining work
+                Err(_) => Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({"error":"Request timed out"})),
+                    StatusCode::GATEWAY_TIMEOUT,
+                )
+                .into_response()),
+            }
+        })
+    }
+}
+
+// Unit Tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tests::remove_db_files;
+    use crate::routes::restaurant_routes;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    // Build a request timeout layer with the given default and maximum, in milliseconds
+    fn request_timeout(default_ms: u64, max_ms: u64) -> RequestTimeout<()> {
+        let config = Config {
+            request_timeout_ms: default_ms,
+            max_request_timeout_ms: max_ms,
+            ..Config::default()
+        };
+        RequestTimeout::new(&config, ())
+    }
+
+    // Build headers carrying an X-Request-Timeout-Ms value
+    fn headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(REQUEST_TIMEOUT_HEADER, value.parse().unwrap());
+        headers
+    }
+
+    // Test Case: The header overrides the default, is capped, and is ignored when malformed
+    #[test]
+    fn test_timeout_for() {
+        let layer = request_timeout(1000, 5000);
+        let millis = |headers: &HeaderMap| layer.timeout_for(headers).map(|t| t.as_millis());
+        assert_eq!(millis(&HeaderMap::new()), Some(1000));
+        assert_eq!(millis(&headers(" 250 ")), Some(250));
+        assert_eq!(millis(&headers("60000")), Some(5000));
+        for malformed in ["soon", "-5", "0", "1.5"] {
+            assert_eq!(millis(&headers(malformed)), Some(1000), "{}", malformed);
+        }
+
+        // Without a default only requests carrying the header get a timeout
+        let layer = request_timeout(0, 5000);
+        assert_eq!(layer.timeout_for(&HeaderMap::new()), None);
+        assert_eq!(
+            layer.timeout_for(&headers("100")),
+            Some(Duration::from_millis(100))
+        );
+    }
+
+    // Test Case: A short X-Request-Timeout-Ms on a slow request returns 504, a long one succeeds
+    #[tokio::test]
+    async fn test_request_timeout_on_slow_request() {
+        // The chaos delay makes every request take at least 200ms
+        let path = std::env::temp_dir().join(format!(
+            "restaurant-test-{}-request-timeout.db",
+            std::process::id()
+        ));
+        let config = Arc::new(Config {
+            db_path: path.to_string_lossy().into_owned(),
+            chaos_delay_ms: 200,
+            ..Config::default()
+        });
+        let routes = restaurant_routes(config.clone(), Instant::now());
+        let mut service = RequestTimeout::new(&config, warp::service(routes));
+
+        for (timeout_ms, status) in [
+            ("50", StatusCode::GATEWAY_TIMEOUT),
+            ("5000", StatusCode::OK),
+        ] {
+            let req = Request::get("/")
+                .header(REQUEST_TIMEOUT_HEADER, timeout_ms)
+                .body(Body::empty())
+                .unwrap();
+            let resp = service.call(req).await.unwrap();
+            assert_eq!(resp.status(), status);
+        }
+
+        remove_db_files(&path);
+    }
+}
@@ -7,16 +7,16 @@ async fn create_tables() -> Vec<i64> {
     // Create a new HTTP client
     let client = Client::new();
     // Define table codes to be created
-    let table_codes = vec!["T-01", "T-02", "T-03", "T-04", "T-05"];
+    let table_codes = ["T-01", "T-02", "T-03", "T-04", "T-05"];
     // Vector to store the IDs of created tables
     let mut table_ids = Vec::new();
 
     // Iterate over the table codes and create tables
-    for index in 0..5 {
+    for table_code in table_codes {
         // Make a POST request to create a table
         let response: Value = client
             .post("http://localhost:3030/tables/create")
-            .json(&serde_json::json!({"code": table_codes[index]})) // Send table code in the request body
+            .json(&serde_json::json!({"code": table_code})) // Send table code in the request body
             .send()
             .await
             .expect("Failed to create table") // Handle request failure
@@ -29,7 +29,7 @@ async fn create_tables() -> Vec<i64> {
     }
 
     // Return the vector of table IDs
-    return table_ids;
+    table_ids
 }
 
 async fn create_menus() -> Vec<i64> {
@@ -41,11 +41,11 @@ async fn create_menus() -> Vec<i64> {
     let mut menu_ids = Vec::new();
 
     // Iterate over the menu names and create menus
-    for index in 0..5 {
+    for menu_name in menu_names {
         // Make a POST request to create a menu
         let response: Value = client
             .post("http://localhost:3030/menus/create")
-            .json(&serde_json::json!({"name": menu_names[index]})) // Send menu name in the request body
+            .json(&serde_json::json!({"name": menu_name})) // Send menu name in the request body
             .send()
             .await
             .expect("Failed to create menu") // Handle request failure
@@ -58,7 +58,7 @@ async fn create_menus() -> Vec<i64> {
     }
 
     // Return the vector of menu IDs
-    return menu_ids;
+    menu_ids
 }
 
 async fn order_simulation(client: &Client, table_ids: &[i64], menu_ids: &[i64]) {
@@ -97,7 +97,7 @@ async fn order_simulation(client: &Client, table_ids: &[i64], menu_ids: &[i64])
 
                 // 2. Retrieve all items from the order by table ID
                 let response = client
-                    .get(&format!("http://localhost:3030/tables/{}/items", table_id))
+                    .get(format!("http://localhost:3030/tables/{}/items", table_id))
                     .send()
                     .await
                     .expect("Failed to get all items") // Handle request failure
@@ -127,7 +127,7 @@ async fn order_simulation(client: &Client, table_ids: &[i64], menu_ids: &[i64])
                 // 3. Retrieve a specific item from the table by menu ID
                 if let Some(menu_id) = menu_subarray.first() {
                     let response = client
-                        .get(&format!(
+                        .get(format!(
                             "http://localhost:3030/tables/{}/items/{}",
                             table_id, *menu_id
                         ))
@@ -151,7 +151,7 @@ async fn order_simulation(client: &Client, table_ids: &[i64], menu_ids: &[i64])
                 // 4. Remove one item from the table by menu ID
                 if let Some(menu_id) = menu_subarray.first() {
                     let response = client
-                        .delete(&format!(
+                        .delete(format!(
                             "http://localhost:3030/orders/{}/items/{}",
                             table_id, *menu_id
                         ))